@@ -3,13 +3,13 @@ use std::sync::Arc;
 use tokio::time::Duration;
 use std::collections::HashSet;
 use hex;
+use async_trait::async_trait;
 
 use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use rust_decimal::RoundingStrategy;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::prelude::FromPrimitive;
 
@@ -21,30 +21,38 @@ use crate::oms::account::AccountSnapshot;
 use std::str::FromStr;
 
 use hyperliquid_rust_sdk::{
-    BaseUrl,
     ExchangeClient,
     ClientLimit,
     ClientOrder,
     ClientOrderRequest,
     ClientCancelRequest,
     ClientCancelRequestCloid,
+    ClientModifyRequest,
     InfoClient,
+    OrderStatusResponse,
+    BasicOrderInfo,
     Subscription,
     Message,
     Meta,
     ExchangeResponseStatus,
     ExchangeDataStatus
 };
-use ethers::types::H160;
+use ethers::contract::{Eip712, EthAbiType};
+use ethers::types::transaction::eip712::Eip712 as _;
+use ethers::types::{H160, H256};
+use serde::Serialize;
 use alloy::primitives::Address;
 use tokio::sync::mpsc::UnboundedSender;
 use ethers::signers::Signer;
 use uuid::Uuid;
 
 use crate::broker::{Broker};
-use crate::broker::types::BrokerCommand;
-use crate::oms::order::{OrderId, Side};
+use crate::broker::types::{BrokerCommand, LimitOrder, VenueFill, VenueOrder, VenuePosition, VenueState};
+use crate::oms::order::{CancelRejectReason, OrderFlags, OrderId, RejectReason, Side, TimeInForce};
 use crate::oms::event::OmsEvent;
+use crate::oms::fill::{Fill, Liquidity};
+use crate::market::hyperliquid::base_url;
+use crate::market::instrument::{InstrumentRegistry, Network};
 
 
 use std::collections::HashMap;
@@ -52,35 +60,6 @@ use anyhow::Result;
 
 type HlWallet = Wallet<SigningKey>;
 
-#[derive(Debug, Clone)]
-pub struct SymbolRules {
-    pub tick: Decimal,
-    pub sz_decimals: u32,
-}
-
-pub async fn build_symbol_rules(
-    base_url: BaseUrl,
-) -> anyhow::Result<HashMap<String, SymbolRules>> {
-    let info = InfoClient::new(None, Some(base_url)).await?;
-    let meta = info.meta().await?;
-
-    let mut map = HashMap::new();
-
-    for asset in &meta.universe {
-        info!("asset name {} asset sz {}", asset.name.clone(), asset.sz_decimals);
-        map.insert(
-            asset.name.clone(),
-            SymbolRules {
-                tick: Decimal::new(5, 1), // 0.5 ← PERP RULE
-                sz_decimals: asset.sz_decimals,
-            },
-        );
-    }
-
-    Ok(map)
-}
-
-
 pub struct HyperliquidBroker {
     tx: mpsc::Sender<BrokerCommand>,
     rx: Mutex<Option<mpsc::Receiver<BrokerCommand>>>,
@@ -92,14 +71,27 @@ pub struct HyperliquidBroker {
     // read-only state
     info_client: Arc<InfoClient>,
     address: H160,
+    network: Network,
 
-    rules: HashMap<String, SymbolRules>,
+    /// Tick, lot and precision per symbol, shared with the OMS
+    instruments: Arc<InstrumentRegistry>,
+
+    /// Exchange oid per OMS order, needed for modify (HL modifies by oid)
+    oids: Arc<Mutex<HashMap<OrderId, u64>>>,
+
+    /// Orders with a modify in flight, with the cancel status held back
+    /// while it is (HL cancels the old oid before the reply comes back)
+    modifying: Arc<Mutex<HashMap<OrderId, Option<String>>>>,
+
+    /// Trade hashes already reported to the OMS
+    seen_trades: Arc<Mutex<HashSet<String>>>,
 }
 
 impl HyperliquidBroker {
     pub async fn new(
         wallet: HlWallet,
-        base_url: BaseUrl,
+        network: Network,
+        instruments: Arc<InstrumentRegistry>,
         tx: mpsc::Sender<BrokerCommand>,
         rx: Mutex<Option<mpsc::Receiver<BrokerCommand>>>,
         oms_tx: mpsc::Sender<OmsEvent>, 
@@ -110,18 +102,14 @@ impl HyperliquidBroker {
         let client = ExchangeClient::new(
             None,
             wallet,
-            Some(base_url),
+            Some(base_url(network)),
             None,
             None,
         )
             .await?;
 
-        let rules =  build_symbol_rules(BaseUrl::Testnet)
-            .await
-            .expect("failed to load symbol rules");
-
         // info / read-only client
-        let info_client = InfoClient::with_reconnect(None, None).await?;
+        let info_client = InfoClient::with_reconnect(None, Some(base_url(network))).await?;
 
         Ok(Self {
             tx,
@@ -130,41 +118,191 @@ impl HyperliquidBroker {
             client: Arc::new(client),
             info_client: Arc::new(info_client),
             address,
-            rules,
+            network,
+            instruments,
+            oids: Arc::new(Mutex::new(HashMap::new())),
+            modifying: Arc::new(Mutex::new(HashMap::new())),
+            seen_trades: Arc::new(Mutex::new(HashSet::new())),
         })
     }
 
     fn quantize_price(&self, symbol: &str, price: Decimal) -> Decimal {
-        // let tick = self.rules[symbol].tick;
-        // (price / tick).floor() * tick
-        // Hyperliquid Perp Rule: Max decimals = 6 - szDecimals
-        let max_decimals = 6 - self.rules[symbol].sz_decimals;
-
-        // 1. Apply the decimal cap
-        let mut p = price.round_dp_with_strategy(max_decimals, RoundingStrategy::ToZero);
-
-        // 2. Apply the 5 Significant Figures Rule (Exchange-wide cap)
-        let s_figs = p.to_string()
-            .replace(".", "")
-            .trim_start_matches('0')
-            .len();
-
-        if s_figs > 5 {
-            let current_scale = p.scale();
-            p = p.round_dp_with_strategy(
-                current_scale - (s_figs as u32 - 5), 
-                RoundingStrategy::ToZero
-            );
+        self.instruments.get(symbol).map_or(price, |i| i.truncate_price(price))
+    }
+
+    fn quantize_qty(&self, symbol: &str, qty: Decimal) -> Decimal {
+        self.instruments.get(symbol).map_or(qty, |i| i.round_qty(qty))
+    }
+
+    /// Places `orders` in one bulk request. HL answers with one status per
+    /// order, in the order sent.
+    async fn place_orders(&self, orders: Vec<LimitOrder>) {
+        let mut sent: Vec<(OrderId, Decimal)> = Vec::new();
+        let mut requests = Vec::new();
+
+        for LimitOrder { order_id, symbol, side, qty, price, tif, flags } in orders {
+            if !self.instruments.contains(&symbol) {
+                warn!("[BROKER][HL] unknown symbol {} for order {:?}", symbol, order_id);
+                let _ = self.oms_tx
+                    .send(OmsEvent::OrderRejected { order_id, reason: RejectReason::UnknownSymbol })
+                    .await;
+                continue;
+            }
+
+            let price_dec = self.quantize_price(&symbol, price);
+            let qty_dec   = self.quantize_qty(&symbol, qty);
+
+            // IMPORTANT: convert only after quantization
+            requests.push(ClientOrderRequest {
+                asset: symbol,
+                is_buy: matches!(side, Side::Buy),
+                reduce_only: flags.reduce_only,
+                limit_px: price_dec.to_f64().expect("price not representable as f64"),
+                sz: qty_dec.to_f64().expect("qty not representable as f64"),
+                cloid: Some(order_id.0), // ← use OMS order_id
+                order_type: ClientOrder::Limit(ClientLimit {
+                    tif: hl_tif(tif).to_string(),
+                }),
+            });
+            sent.push((order_id, qty_dec));
+        }
+
+        if requests.is_empty() {
+            return;
         }
 
-        p.normalize() // Removes trailing zeros for the API
+        let statuses = match self.client.bulk_order(requests, None).await {
+            Ok(ExchangeResponseStatus::Ok(resp)) => resp.data.map(|d| d.statuses).unwrap_or_default(),
+
+            // the action as a whole was refused
+            Ok(ExchangeResponseStatus::Err(e)) => {
+                info!("[BROKER][HL] {} orders rejected → {}", sent.len(), e);
+                let reason = parse_reject(&e);
+                for (order_id, _) in sent {
+                    let _ = self.oms_tx
+                        .send(OmsEvent::OrderRejected { order_id, reason: reason.clone() })
+                        .await;
+                }
+                return;
+            }
+
+            Err(e) => {
+                info!("[BROKER][HL] {} orders transport failed → {:?}", sent.len(), e);
+                // they may have reached the book anyway
+                for (order_id, _) in sent {
+                    let _ = self.oms_tx.send(OmsEvent::OrderStatusUnknown { order_id }).await;
+                }
+                return;
+            }
+        };
+
+        for (i, (order_id, qty)) in sent.into_iter().enumerate() {
+            let status = match statuses.get(i) {
+                Some(ExchangeDataStatus::Error(e)) => {
+                    info!("[BROKER][HL] order {:?} rejected → {}", order_id, e);
+                    // DO NOT send OrderAccepted
+                    let _ = self.oms_tx
+                        .send(OmsEvent::OrderRejected { order_id, reason: parse_reject(e) })
+                        .await;
+                    continue;
+                }
+                Some(status) => status,
+                None => {
+                    warn!("[BROKER][HL] no status for order {:?}", order_id);
+                    let _ = self.oms_tx.send(OmsEvent::OrderStatusUnknown { order_id }).await;
+                    continue;
+                }
+            };
+
+            let (oid, filled) = match status {
+                ExchangeDataStatus::Resting(o) => (Some(o.oid), None),
+                ExchangeDataStatus::Filled(f) => (Some(f.oid), Decimal::from_str(&f.total_sz).ok()),
+                _ => (None, None),
+            };
+            if let Some(oid) = oid {
+                self.oids.lock().await.insert(order_id, oid);
+            }
+
+            let _ = self.oms_tx.send(OmsEvent::OrderAccepted { order_id }).await;
+            info!("[BROKER][HL] order {:?} accepted → {:?}", order_id, status);
+
+            // whatever did not trade or rest is gone (IOC remainder,
+            // reduce-only trimmed by the venue)
+            let resting = matches!(status, ExchangeDataStatus::Resting(_));
+            if !resting && filled.unwrap_or(dec!(0)) < qty {
+                let _ = self.oms_tx.send(OmsEvent::OrderExpired { order_id }).await;
+            }
+        }
     }
 
-    fn quantize_qty(&self, symbol: &str, qty: Decimal) -> Decimal {
-        let decimals = self.rules[symbol].sz_decimals;
-        qty.round_dp_with_strategy(decimals, RoundingStrategy::ToZero)
+    /// Cancels `orders` with at most two bulk requests: by exchange oid
+    /// where we have one (adopted orders may have no cloid of ours), else
+    /// by cloid
+    async fn cancel_orders(&self, orders: Vec<(OrderId, String)>) {
+        let known = self.oids.lock().await.clone();
+        let (by_oid, by_cloid): (Vec<_>, Vec<_>) = orders
+            .into_iter()
+            .map(|(order_id, symbol)| (order_id, known.get(&order_id).copied(), symbol))
+            .partition(|(_, oid, _)| oid.is_some());
+
+        if !by_oid.is_empty() {
+            let cancels = by_oid
+                .iter()
+                .filter_map(|(_, oid, symbol)| oid.map(|oid| ClientCancelRequest { asset: symbol.clone(), oid }))
+                .collect();
+            let res = self.client.bulk_cancel(cancels, None).await;
+            self.report_cancels(by_oid.into_iter().map(|(id, oid, _)| (id, oid)).collect(), res).await;
+        }
+
+        if !by_cloid.is_empty() {
+            let cancels = by_cloid
+                .iter()
+                .map(|(order_id, _, symbol)| ClientCancelRequestCloid { asset: symbol.clone(), cloid: order_id.0 })
+                .collect();
+            let res = self.client.bulk_cancel_by_cloid(cancels, None).await;
+            self.report_cancels(by_cloid.into_iter().map(|(id, oid, _)| (id, oid)).collect(), res).await;
+        }
     }
 
+    async fn report_cancels(
+        &self,
+        orders: Vec<(OrderId, Option<u64>)>,
+        res: Result<ExchangeResponseStatus, hyperliquid_rust_sdk::Error>,
+    ) {
+        let statuses = match res {
+            Ok(ExchangeResponseStatus::Ok(resp)) => resp.data.map(|d| d.statuses).unwrap_or_default(),
+            Ok(ExchangeResponseStatus::Err(e)) => {
+                info!("[BROKER][HL] {} cancels rejected → {}", orders.len(), e);
+                Vec::new()
+            }
+            Err(e) => {
+                info!("[BROKER][HL] {} cancels transport failed → {:?}", orders.len(), e);
+                for (order_id, _) in orders {
+                    let reason = CancelRejectReason::Transport(e.to_string());
+                    let _ = self.oms_tx.send(OmsEvent::CancelRejected { order_id, reason }).await;
+                }
+                return;
+            }
+        };
+
+        for (i, (order_id, oid)) in orders.into_iter().enumerate() {
+            let event = match statuses.get(i) {
+                Some(ExchangeDataStatus::Error(_)) | None => {
+                    // HL gives one error for "filled", "already cancelled"
+                    // and "never placed" — ask what actually happened
+                    info!(
+                        "[BROKER][HL] cancel {:?} rejected → {:?}",
+                        order_id,
+                        statuses.get(i)
+                    );
+                    resolve_cancel_reject(&self.client, self.address, order_id, oid).await
+                }
+                Some(_) => OmsEvent::CancelConfirmed { order_id },
+            };
+
+            let _ = self.oms_tx.send(event).await;
+        }
+    }
 }
 
 async fn fetch_account_snapshot(
@@ -179,11 +317,15 @@ async fn fetch_account_snapshot(
     let raw_usd = Decimal::from_str(&cms.total_raw_usd)?;
     let unrealized_pnl = equity - raw_usd;
 
-    let mut net_position = dec!(0);
+    let mut positions = HashMap::new();
+    let mut entry_prices = HashMap::new();
     for ap in &state.asset_positions {
-        if ap.position.coin == "TST" {
-            net_position = Decimal::from_str(&ap.position.szi)?;
-            break;
+        positions.insert(
+            ap.position.coin.clone(),
+            Decimal::from_str(&ap.position.szi)?,
+        );
+        if let Some(px) = ap.position.entry_px.as_deref() {
+            entry_prices.insert(ap.position.coin.clone(), Decimal::from_str(px)?);
         }
     }
 
@@ -193,7 +335,8 @@ async fn fetch_account_snapshot(
         available_margin: Decimal::from_str(&state.withdrawable)?,
         unrealized_pnl,
         realized_pnl: dec!(0), // HL doesn’t expose cleanly
-        net_position,
+        positions,
+        entry_prices,
     })
 }
 
@@ -212,7 +355,372 @@ fn has_error_status(r: &ExchangeResponseStatus) -> bool {
     }
 }
 
+fn hl_tif(tif: TimeInForce) -> &'static str {
+    match tif {
+        TimeInForce::Gtc => "Gtc",
+        TimeInForce::Ioc => "Ioc",
+        TimeInForce::Alo => "Alo",
+    }
+}
+
+/// Typed reason from HL's reject text; anything unrecognised is kept verbatim
+fn parse_reject(msg: &str) -> RejectReason {
+    let lower = msg.to_ascii_lowercase();
+
+    if lower.contains("insufficient margin") {
+        RejectReason::InsufficientMargin
+    } else if lower.contains("post only order would have immediately matched") {
+        RejectReason::PostOnlyWouldCross
+    } else if lower.contains("away from the reference price") {
+        RejectReason::PriceOutOfBand
+    } else if lower.contains("too many") || lower.contains("rate limit") {
+        RejectReason::RateLimited
+    } else {
+        RejectReason::Unknown(msg.to_string())
+    }
+}
+
+/// Typed reason for an `orderUpdates` `...Rejected` status
+fn parse_reject_status(status: &str) -> RejectReason {
+    match status {
+        "perpMarginRejected" | "insufficientSpotBalanceRejected" => RejectReason::InsufficientMargin,
+        "badAloPxRejected" => RejectReason::PostOnlyWouldCross,
+        "oracleRejected" => RejectReason::PriceOutOfBand,
+        _ => RejectReason::Unknown(status.to_string()),
+    }
+}
+
+/// Whether an `orderUpdates` status means the venue took the order off the
+/// book without it filling: our own cancels and its own (margin, self-trade,
+/// reduce-only, scheduled cancel, ...)
+fn is_cancel_status(status: &str) -> bool {
+    status == "canceled" || status.ends_with("Canceled") || status == "scheduledCancel"
+}
+
+/// OMS event for one `orderUpdates` status. `None` when there is nothing to
+/// tell: fills carry their own events on the fills feed.
+fn order_update_event(order_id: OrderId, status: &str) -> Option<OmsEvent> {
+    match status {
+        "open" => Some(OmsEvent::OrderAccepted { order_id }),
+        "filled" | "triggered" => None,
+        "rejected" => Some(OmsEvent::OrderRejected {
+            order_id,
+            reason: RejectReason::Unknown(status.to_string()),
+        }),
+        s if s.ends_with("Rejected") => Some(OmsEvent::OrderRejected {
+            order_id,
+            reason: parse_reject_status(s),
+        }),
+        s if is_cancel_status(s) => Some(OmsEvent::OrderExpired { order_id }),
+        _ => None,
+    }
+}
+
+/// Holds back a cancel status for an order with a modify in flight: it may
+/// be the venue retiring the old oid. Returns whether it was held.
+fn hold_cancel(modifying: &mut HashMap<OrderId, Option<String>>, order_id: OrderId, status: &str) -> bool {
+    match modifying.get_mut(&order_id) {
+        Some(held) if is_cancel_status(status) => {
+            *held = Some(status.to_string());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Ends the in-flight modify of `order_id`. A held cancel belonged to the
+/// replace when it went through; otherwise the order really is gone.
+fn resolve_modify(
+    modifying: &mut HashMap<OrderId, Option<String>>,
+    order_id: OrderId,
+    accepted: bool,
+) -> Option<OmsEvent> {
+    let held = modifying.remove(&order_id).flatten()?;
+    if accepted {
+        None
+    } else {
+        order_update_event(order_id, &held)
+    }
+}
+
+/// Exchange oid of the first resting/filled status in a response
+fn first_oid(r: &ExchangeResponseStatus) -> Option<u64> {
+    match r {
+        ExchangeResponseStatus::Ok(resp) => resp.data.as_ref().and_then(|d| {
+            d.statuses.iter().find_map(|s| match s {
+                ExchangeDataStatus::Resting(o) => Some(o.oid),
+                ExchangeDataStatus::Filled(o) => Some(o.oid),
+                _ => None,
+            })
+        }),
+        ExchangeResponseStatus::Err(_) => None,
+    }
+}
+
+/// Id for a resting order we did not place. Derived from the exchange oid
+/// so it stays the same across restarts; the zero high half keeps it clear
+/// of the random ids we give our own orders.
+fn foreign_order_id(oid: u64) -> OrderId {
+    OrderId(Uuid::from_u64_pair(0, oid))
+}
+
+/// OMS order a WS fill or update belongs to: ours by cloid, or a foreign
+/// order the OMS tracks since startup. `None` for orders it never saw.
+fn ws_order_id(cloid: Option<&str>, oid: u64, oids: &HashMap<OrderId, u64>) -> Option<OrderId> {
+    match cloid {
+        Some(cloid) => parse_cloid(cloid),
+        None => Some(foreign_order_id(oid)).filter(|id| oids.contains_key(id)),
+    }
+}
+
+/// Our OrderId from an HL cloid ("0x" + 32 hex chars)
+fn parse_cloid(cloid: &str) -> Option<OrderId> {
+    let cloid = cloid.strip_prefix("0x").unwrap_or(cloid);
+    if cloid.len() != 32 {
+        return None;
+    }
+    let bytes = hex::decode(cloid).ok()?;
+    Uuid::from_slice(&bytes).ok().map(OrderId)
+}
+
+/// HL reports sides as "B" (bid) and "A" (ask)
+fn parse_side(side: &str) -> Side {
+    if side == "B" { Side::Buy } else { Side::Sell }
+}
+
+fn parse_tif(tif: &str) -> TimeInForce {
+    match tif {
+        "Ioc" => TimeInForce::Ioc,
+        "Alo" => TimeInForce::Alo,
+        _ => TimeInForce::Gtc,
+    }
+}
+
+/// Looks an order up by exchange oid if we have one, else by cloid. HL's
+/// info API takes the cloid in place of the oid, which the SDK's
+/// `query_order_by_oid` does not allow.
+async fn query_order_status(
+    client: &ExchangeClient,
+    address: H160,
+    order_id: OrderId,
+    oid: Option<u64>,
+) -> anyhow::Result<OrderStatusResponse> {
+    let oid = match oid {
+        Some(oid) => serde_json::json!(oid),
+        None => serde_json::json!(format!("0x{}", order_id.0.simple())),
+    };
+    let body = serde_json::json!({
+        "type": "orderStatus",
+        "user": address,
+        "oid": oid,
+    });
+
+    let raw = client.http_client.post("/info", body.to_string()).await?;
+    Ok(serde_json::from_str(&raw)?)
+}
+
+/// Turns a refused cancel into what the OMS should do with the order
+async fn resolve_cancel_reject(
+    client: &ExchangeClient,
+    address: H160,
+    order_id: OrderId,
+    oid: Option<u64>,
+) -> OmsEvent {
+    let status = match query_order_status(client, address, order_id, oid).await {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("[BROKER][HL] status query {:?} failed → {:?}", order_id, e);
+            return OmsEvent::CancelRejected {
+                order_id,
+                reason: CancelRejectReason::Transport(e.to_string()),
+            };
+        }
+    };
+
+    let Some(order) = status.order else {
+        info!("[BROKER][HL] {:?} unknown to venue ({})", order_id, status.status);
+        return OmsEvent::CancelRejected {
+            order_id,
+            reason: CancelRejectReason::UnknownOrder,
+        };
+    };
+
+    info!("[BROKER][HL] {:?} venue status {}", order_id, order.status);
+
+    match order.status.as_str() {
+        "filled" => OmsEvent::CancelRejected {
+            order_id,
+            reason: CancelRejectReason::AlreadyFilled,
+        },
+        "open" | "triggered" => OmsEvent::CancelRejected {
+            order_id,
+            reason: CancelRejectReason::Refused(order.status),
+        },
+        // canceled, marginCanceled, rejected, ... — it is gone either way
+        _ => OmsEvent::CancelConfirmed { order_id },
+    }
+}
+
+/// HL refuses a scheduled cancel less than this far out
+const MIN_SCHEDULE_AHEAD: Duration = Duration::from_secs(5);
+
+/// The `scheduleCancel` exchange action, which the SDK does not wrap
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum ScheduleCancel {
+    ScheduleCancel {
+        /// Venue time (ms) to cancel everything at; `None` clears it
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time: Option<u64>,
+    },
+}
+
+/// EIP-712 envelope HL expects around an L1 action hash
+#[derive(Debug, Clone, Eip712, EthAbiType)]
+#[eip712(
+    name = "Exchange",
+    version = "1",
+    chain_id = 1337,
+    verifying_contract = "0x0000000000000000000000000000000000000000"
+)]
+struct Agent {
+    source: String,
+    connection_id: H256,
+}
+
+/// Arms (or with `None` clears) the venue's cancel-all at `time_ms`. Signed
+/// here the way the SDK signs its own L1 actions.
+async fn schedule_cancel(client: &ExchangeClient, time_ms: Option<u64>) -> anyhow::Result<()> {
+    let action = ScheduleCancel::ScheduleCancel { time: time_ms };
+    let nonce = chrono::Utc::now().timestamp_millis() as u64;
+
+    let mut bytes = rmp_serde::to_vec_named(&action)?;
+    bytes.extend(nonce.to_be_bytes());
+    match client.vault_address {
+        Some(vault) => {
+            bytes.push(1);
+            bytes.extend(vault.to_fixed_bytes());
+        }
+        None => bytes.push(0),
+    }
+
+    let agent = Agent {
+        source: if client.http_client.is_mainnet() { "a" } else { "b" }.to_string(),
+        connection_id: H256(ethers::utils::keccak256(bytes)),
+    };
+    let hash = agent.encode_eip712().map_err(|e| anyhow::anyhow!("eip712: {}", e))?;
+    let signature = client.wallet.sign_hash(H256(hash))?;
+
+    let body = serde_json::json!({
+        "action": action,
+        "signature": signature,
+        "nonce": nonce,
+        "vaultAddress": client.vault_address,
+    });
+    let raw = client.http_client.post("/exchange", body.to_string()).await?;
+
+    match serde_json::from_str::<ExchangeResponseStatus>(&raw)? {
+        ExchangeResponseStatus::Ok(_) => Ok(()),
+        ExchangeResponseStatus::Err(e) => anyhow::bail!("{}", e),
+    }
+}
+
+#[async_trait]
 impl Broker for HyperliquidBroker {
+    async fn venue_state(&self) -> anyhow::Result<VenueState> {
+        let info = &self.info_client;
+
+        // ---- positions ----
+        let state = info.user_state(self.address).await?;
+        let mut positions = HashMap::new();
+        for ap in &state.asset_positions {
+            let p = &ap.position;
+            positions.insert(
+                p.coin.clone(),
+                VenuePosition {
+                    net_qty: Decimal::from_str(&p.szi)?,
+                    entry_price: p
+                        .entry_px
+                        .as_deref()
+                        .map(Decimal::from_str)
+                        .transpose()?
+                        .unwrap_or(dec!(0)),
+                },
+            );
+        }
+
+        // ---- open orders (frontend variant carries cloid and orig size) ----
+        let body = serde_json::json!({
+            "type": "frontendOpenOrders",
+            "user": self.address,
+        });
+        let raw = self.client.http_client.post("/info", body.to_string()).await?;
+        let resting: Vec<BasicOrderInfo> = serde_json::from_str(&raw)?;
+
+        let mut oids = self.oids.lock().await;
+        let mut open_orders = Vec::with_capacity(resting.len());
+        for o in resting {
+            // not placed by us: give it an id, and cancel/modify go by oid
+            let order_id = o
+                .cloid
+                .as_deref()
+                .and_then(parse_cloid)
+                .unwrap_or_else(|| foreign_order_id(o.oid));
+            oids.insert(order_id, o.oid);
+
+            open_orders.push(VenueOrder {
+                order_id,
+                symbol: o.coin,
+                side: parse_side(&o.side),
+                price: Decimal::from_str(&o.limit_px)?,
+                original_qty: Decimal::from_str(&o.orig_sz)?,
+                remaining_qty: Decimal::from_str(&o.sz)?,
+                tif: parse_tif(&o.tif),
+                flags: OrderFlags { reduce_only: o.reduce_only },
+            });
+        }
+
+        // ---- recent fills; REST fills have no cloid, map through oids ----
+        let by_oid: HashMap<u64, OrderId> = info
+            .historical_orders(self.address)
+            .await?
+            .into_iter()
+            .filter_map(|o| {
+                let id = o.order.cloid.as_deref().and_then(parse_cloid)?;
+                Some((o.order.oid, id))
+            })
+            .chain(oids.iter().map(|(id, oid)| (*oid, *id)))
+            .collect();
+        drop(oids);
+
+        let mut raw_fills = info.user_fills(self.address).await?;
+        raw_fills.sort_by_key(|f| f.time);
+
+        let mut seen = self.seen_trades.lock().await;
+        let mut fills = Vec::with_capacity(raw_fills.len());
+        for f in raw_fills {
+            // the WS snapshot repeats these; they are already in the position
+            seen.insert(f.hash.clone());
+
+            fills.push(VenueFill {
+                order_id: by_oid.get(&f.oid).copied(),
+                symbol: f.coin,
+                side: parse_side(&f.side),
+                qty: Decimal::from_str(&f.sz)?,
+                price: Decimal::from_str(&f.px)?,
+            });
+        }
+
+        info!(
+            "[BROKER][HL] venue state: {} open orders, {} positions, {} fills",
+            open_orders.len(),
+            positions.len(),
+            fills.len()
+        );
+
+        Ok(VenueState { open_orders, positions, fills })
+    }
+
     fn command_sender(&self) -> mpsc::Sender<BrokerCommand> {
         self.tx.clone()
     }
@@ -225,9 +733,13 @@ impl Broker for HyperliquidBroker {
         let oms_tx_balance = self.oms_tx.clone();
         let info_client = self.info_client.clone();
         let address = self.address;
+        let network = self.network;
 
-        let seen_trades = Arc::new(Mutex::new(HashSet::<String>::new()));
-        let seen_trades_ws = seen_trades.clone();
+        let seen_trades_ws = self.seen_trades.clone();
+        let oids = self.oids.clone();
+        let oids_ws = self.oids.clone();
+        let modifying = self.modifying.clone();
+        let modifying_ws = self.modifying.clone();
 
         // ===============================
         // REST COMMAND LOOP
@@ -242,113 +754,208 @@ impl Broker for HyperliquidBroker {
             .take()
             .expect("broker already started");
 
+            // venue time the scheduled cancel-all fires at, while armed
+            let mut dead_man_at: Option<u64> = None;
+
             while let Some(cmd) = rx.recv().await {
                 match cmd {
                     BrokerCommand::PlaceLimit {
                         order_id,
+                        symbol,
                         side,
                         qty,
                         price,
+                        tif,
+                        flags,
                     } => {
-                        let is_buy = matches!(side, Side::Buy);
-                        let symbol = "TST";
-
-                        let price_dec = self.quantize_price(symbol, price);
-                        let qty_dec   = self.quantize_qty(symbol, qty);
+                        self.place_orders(vec![LimitOrder { order_id, symbol, side, qty, price, tif, flags }])
+                            .await;
+                    }
 
-                        // IMPORTANT: convert only after quantization
-                        let price_f64 = price_dec
-                            .to_f64()
-                            .expect("price not representable as f64");
+                    BrokerCommand::PlaceBatch { orders } => {
+                        info!("[BROKER][HL] placing batch of {}", orders.len());
+                        self.place_orders(orders).await;
+                    }
 
-                        let qty_f64 = qty_dec
-                            .to_f64()
-                            .expect("qty not representable as f64");
+                    BrokerCommand::Modify {
+                        order_id,
+                        symbol,
+                        side,
+                        qty,
+                        price,
+                        tif,
+                        flags,
+                    } => {
+                        let oid = oids.lock().await.get(&order_id).copied();
+
+                        let oid = match (oid, self.instruments.contains(&symbol)) {
+                            (Some(oid), true) => oid,
+                            _ => {
+                                warn!(
+                                    "[BROKER][HL] cannot modify {:?} {} — no exchange oid or unknown symbol",
+                                    order_id, symbol
+                                );
+                                let _ = oms_tx
+                                    .send(OmsEvent::AmendRejected { order_id })
+                                    .await;
+                                continue;
+                            }
+                        };
 
-                        let order = ClientOrderRequest {
-                            asset: symbol.to_string(), // ← for now, hardcoded
-                            is_buy,
-                            reduce_only: false,
-                            limit_px: price_f64,
-                            sz: qty_f64,
-                            cloid: Some(order_id.0), // ← use OMS order_id
-                            order_type: ClientOrder::Limit(ClientLimit {
-                                tif: "Gtc".to_string(),
-                            }),
+                        let price_dec = self.quantize_price(&symbol, price);
+                        let qty_dec   = self.quantize_qty(&symbol, qty);
+
+                        let modify = ClientModifyRequest {
+                            oid,
+                            order: ClientOrderRequest {
+                                asset: symbol,
+                                is_buy: matches!(side, Side::Buy),
+                                reduce_only: flags.reduce_only,
+                                limit_px: price_dec
+                                    .to_f64()
+                                    .expect("price not representable as f64"),
+                                sz: qty_dec
+                                    .to_f64()
+                                    .expect("qty not representable as f64"),
+                                cloid: Some(order_id.0), // keep the OMS id across the replace
+                                order_type: ClientOrder::Limit(ClientLimit {
+                                    tif: hl_tif(tif).to_string(),
+                                }),
+                            },
                         };
 
-                        let res = client.order(order, None).await;
+                        modifying.lock().await.insert(order_id, None);
+                        let result = client.modify(modify, None).await;
+                        let accepted = matches!(&result, Ok(r) if !has_error_status(r));
+
+                        // track the new oid before the hold is lifted, so a
+                        // late cancel of the old one still reads as stale
+                        if let (true, Ok(r)) = (accepted, &result) {
+                            if let Some(new_oid) = first_oid(r) {
+                                oids.lock().await.insert(order_id, new_oid);
+                            }
+                        }
+                        if let Some(event) = resolve_modify(&mut *modifying.lock().await, order_id, accepted) {
+                            info!("[BROKER][HL] {:?} modify failed after a cancel → {:?}", order_id, event);
+                            let _ = oms_tx.send(event).await;
+                        }
+
+                        match result {
+                            Ok(r) if accepted => {
+
+                                let _ = oms_tx
+                                    .send(OmsEvent::AmendAccepted { order_id })
+                                    .await;
+
+                                info!(
+                                    "[BROKER][HL] modify {:?} accepted → {:?}",
+                                    order_id, r
+                                );
+                            }
 
-                        match res {
                             Ok(r) => {
-                                if has_error_status(&r) {
-                                    let _ = oms_tx
-                                        .send(OmsEvent::OrderRejected { order_id })
-                                        .await;
-                                    info!(
-                                        "[BROKER][HL] order {:?} rejected → {:?}",
-                                        order_id, r
-                                    );
-                                    // DO NOT send OrderAccepted
-                                } else {
-                                    let _ = oms_tx
-                                        .send(OmsEvent::OrderAccepted { order_id })
-                                        .await;
-
-                                    info!(
-                                        "[BROKER][HL] order {:?} accepted → {:?}",
-                                        order_id, r
-                                    );
-                                }
+                                let _ = oms_tx
+                                    .send(OmsEvent::AmendRejected { order_id })
+                                    .await;
+                                info!(
+                                    "[BROKER][HL] modify {:?} rejected → {:?}",
+                                    order_id, r
+                                );
                             }
 
                             Err(e) => {
+                                let _ = oms_tx
+                                    .send(OmsEvent::AmendRejected { order_id })
+                                    .await;
                                 info!(
-                                    "[BROKER][HL] order {:?} transport failed → {:?}",
+                                    "[BROKER][HL] modify {:?} transport failed → {:?}",
                                     order_id, e
                                 );
                             }
                         }
+                    }
 
+                    BrokerCommand::Cancel { order_id, symbol } => {
+                        self.cancel_orders(vec![(order_id, symbol)]).await;
+                    }
 
+                    BrokerCommand::CancelBatch { orders } => {
+                        info!("[BROKER][HL] cancelling batch of {}", orders.len());
+                        self.cancel_orders(orders).await;
                     }
 
-                    BrokerCommand::Cancel { order_id } => {
-                        let cancel = ClientCancelRequestCloid {
-                            asset: "TST".to_string(),
-                            cloid: order_id.0,
+                    BrokerCommand::QueryStatus { order_id, symbol } => {
+                        let oid = oids.lock().await.get(&order_id).copied();
+                        let status = match query_order_status(&client, address, order_id, oid).await {
+                            Ok(s) => s,
+                            Err(e) => {
+                                // OMS asks again after the next timeout
+                                warn!("[BROKER][HL] status query {:?} failed → {:?}", order_id, e);
+                                continue;
+                            }
                         };
 
-                        let res = client.cancel_by_cloid(cancel, None).await;
-                        
-                        match res {
-                            Ok(r) => {
-                                if has_error_status(&r) {
-                                    info!(
-                                        "[BROKER][HL] cancel {:?} rejected → {:?}",
-                                        order_id, r
-                                    );
-                                } else {
-                                    let _ = oms_tx
-                                        .send(OmsEvent::CancelConfirmed { order_id })
-                                        .await;
-                                }
-                            }
+                        info!("[BROKER][HL] {:?} {} status → {:?}", order_id, symbol, status);
 
-                            Err(e) => {
-                                info!(
-                                    "[BROKER][HL] cancel {:?} transport failed → {:?}",
-                                    order_id, e
-                                );
+                        let Some(order) = status.order else {
+                            // not on the book yet, or never will be; the
+                            // OMS decides after a few of these
+                            let _ = oms_tx.send(OmsEvent::OrderNotFound { order_id }).await;
+                            continue;
+                        };
+
+                        oids.lock().await.insert(order_id, order.order.oid);
+
+                        // fills themselves arrive over the WS
+                        let events = match order.status.as_str() {
+                            "open" | "triggered" | "filled" => {
+                                vec![OmsEvent::OrderAccepted { order_id }]
                             }
+                            "rejected" => vec![OmsEvent::OrderRejected {
+                                order_id,
+                                reason: RejectReason::Unknown("rejected".into()),
+                            }],
+                            _ => vec![
+                                OmsEvent::OrderAccepted { order_id },
+                                OmsEvent::OrderExpired { order_id },
+                            ],
+                        };
+
+                        for event in events {
+                            let _ = oms_tx.send(event).await;
                         }
+                    }
+
+                    BrokerCommand::Heartbeat { timeout } => {
+                        let timeout = timeout.max(MIN_SCHEDULE_AHEAD);
+                        let now = chrono::Utc::now().timestamp_millis() as u64;
+                        let ms = timeout.as_millis() as u64;
 
+                        // every refresh is a signed request; push the deadline
+                        // out once half of it has been used up
+                        if dead_man_at.is_some_and(|at| at.saturating_sub(now) > ms / 2) {
+                            continue;
+                        }
 
+                        match schedule_cancel(&client, Some(now + ms)).await {
+                            Ok(()) => {
+                                if dead_man_at.is_none() {
+                                    info!("[BROKER][HL] dead-man armed, cancel-all after {:?} of silence", timeout);
+                                }
+                                dead_man_at = Some(now + ms);
+                            }
+                            Err(e) => {
+                                // whatever was scheduled before still stands
+                                warn!("[BROKER][HL] dead-man refresh failed → {:?}", e);
+                            }
+                        }
                     }
 
-                    BrokerCommand::Flatten { qty, limit_px, } => {
-                        let symbol = "TST";
-                        let is_buy = false; // default
+                    BrokerCommand::Flatten { symbol, qty, limit_px, } => {
+                        if !self.instruments.contains(&symbol) {
+                            error!("[BROKER][HL] cannot flatten unknown symbol {}", symbol);
+                            continue;
+                        }
 
                         let side = if qty > dec!(0) {
                             // qty already abs()'d in OMS
@@ -364,21 +971,21 @@ impl Broker for HyperliquidBroker {
                             .to_f64()
                             .expect("flatten qty not representable");
 
-                        let limit_px = self.quantize_price(symbol, limit_px);
+                        let limit_px = self.quantize_price(&symbol, limit_px);
 
                         let order = ClientOrderRequest {
-                            asset: symbol.to_string(),
+                            asset: symbol.clone(),
                             is_buy,
                             reduce_only: true, // CRITICAL
                             limit_px: limit_px.to_f64().unwrap(),
                             sz: qty_f64,
                             cloid: None,
                             order_type: ClientOrder::Limit(ClientLimit {
-                                tif: "Ioc".to_string(),     // immediate-or-cancel,
+                                tif: hl_tif(TimeInForce::Ioc).to_string(), // immediate-or-cancel,
                             }),
                         };
 
-                        info!("[BROKER][HL] flatten market {:?}", side);
+                        info!("[BROKER][HL] flatten {} market {:?}", symbol, side);
 
                         match client.order(order, None).await {
                             Ok(r) => {
@@ -400,13 +1007,7 @@ impl Broker for HyperliquidBroker {
         
 
         tokio::spawn(async move {
-            let base_url = if client_ws.http_client.base_url.contains("testnet") {
-                BaseUrl::Testnet
-            } else {
-                BaseUrl::Mainnet
-            };
-
-            let mut info = InfoClient::new(None, Some(base_url))
+            let mut info = InfoClient::new(None, Some(base_url(network)))
                 .await
                 .expect("info client");
 
@@ -417,21 +1018,27 @@ impl Broker for HyperliquidBroker {
 
             info.subscribe(
                 Subscription::UserFills { user: wallet_addr },
-                msg_tx,
+                msg_tx.clone(),
             )
                 .await
                 .expect("subscribe user fills");
 
-            info!("[BROKER][HL] WS subscribed to user fills");
+            // exchange-side cancels and rejects only ever show up here
+            info.subscribe(
+                Subscription::OrderUpdates { user: wallet_addr },
+                msg_tx,
+            )
+                .await
+                .expect("subscribe order updates");
+
+            info!("[BROKER][HL] WS subscribed to user fills and order updates");
 
             while let Some(msg) = msg_rx.recv().await {
                 info!("[HL][WS][RAW] {:?}", msg);
                 match msg {
+                    // the snapshot batch is not skipped: fills seen during
+                    // startup reconciliation are deduped, anything newer is real
                     Message::UserFills ( user_fills ) => {
-                        if user_fills.data.is_snapshot == Some(true) {
-                            continue;
-                        }
-
                         for fill in user_fills.data.fills {
                             info!("in fill!!!!!");
                             let mut seen = seen_trades_ws.lock().await;
@@ -443,30 +1050,76 @@ impl Broker for HyperliquidBroker {
                             let price = fill.px.parse::<f64>().unwrap();
 
                             info!("in filler!!!!!");
-                            if let Some(cloid) = fill.cloid.as_deref() {
-
-                                
-                                let cloid = cloid.strip_prefix("0x").unwrap_or(cloid);
-
-                                if cloid.len() == 32 {
-                                    if let Ok(bytes) = hex::decode(cloid) {
-                                        let bytes: Vec<u8> = bytes;
-                                        if let Ok(uuid) = Uuid::from_slice(&bytes) {
-                                            info!("[WS] fill uuid is {}", uuid);
-                                            let _ = oms_tx_ws
-                                                .send(OmsEvent::Fill {
-                                                    order_id: OrderId(uuid),
-                                                    qty: Decimal::from_f64(qty).unwrap(),
-                                                    price: Decimal::from_f64(price).unwrap(),
-                                                })
-                                            .await;
-                                        }
-                                    }
+                            let order_id = {
+                                let mut oids = oids_ws.lock().await;
+                                let order_id = ws_order_id(fill.cloid.as_deref(), fill.oid, &oids);
+                                if let Some(order_id) = order_id {
+                                    oids.insert(order_id, fill.oid);
                                 }
+                                order_id
+                            };
+                            if let Some(order_id) = order_id {
+                                info!("[WS] fill uuid is {}", order_id);
+                                let _ = oms_tx_ws
+                                    .send(OmsEvent::Fill {
+                                        fill: Fill {
+                                            order_id,
+                                            qty: Decimal::from_f64(qty).unwrap(),
+                                            price: Decimal::from_f64(price).unwrap(),
+                                            fee: Decimal::from_str(&fill.fee).unwrap_or_default(),
+                                            fee_currency: fill.fee_token.clone(),
+                                            // crossed means we took liquidity
+                                            liquidity: if fill.crossed { Liquidity::Taker } else { Liquidity::Maker },
+                                            trade_id: fill.tid.to_string(),
+                                            time_ms: fill.time,
+                                        },
+                                    })
+                                .await;
+                            }
+                        }
+                    }
 
+                    Message::OrderUpdates(updates) => {
+                        for update in updates.data {
+                            // orders without a cloid the OMS never adopted are not
+                            // ours (flatten, manual)
+                            let order_id = ws_order_id(update.order.cloid.as_deref(), update.order.oid, &*oids_ws.lock().await);
+                            let Some(order_id) = order_id else {
+                                continue;
+                            };
+                            let status = update.status.as_str();
+
+                            {
+                                if hold_cancel(&mut *modifying_ws.lock().await, order_id, status) {
+                                    info!("[BROKER][HL] {:?} {} for oid {} held until the modify resolves", order_id, status, update.order.oid);
+                                    continue;
+                                }
+
+                                let mut oids = oids_ws.lock().await;
+                                let tracked = oids.get(&order_id).copied();
+
+                                // an amend moves the cloid to a new oid; the
+                                // old one's cancel is not the order's
+                                if is_cancel_status(status) && tracked.is_some_and(|oid| oid != update.order.oid) {
+                                    info!("[BROKER][HL] {:?} stale {} for oid {}", order_id, status, update.order.oid);
+                                    continue;
+                                }
+                                if status == "open" {
+                                    oids.insert(order_id, update.order.oid);
+                                }
+                            }
+
+                            match order_update_event(order_id, status) {
+                                Some(event) => {
+                                    info!("[BROKER][HL] {:?} {} {} → {:?}", order_id, update.order.coin, status, event);
+                                    let _ = oms_tx_ws.send(event).await;
+                                }
+                                None if matches!(status, "filled" | "triggered") => {}
+                                None => warn!("[BROKER][HL] {:?} unhandled order status {}", order_id, status),
                             }
                         }
                     }
+
                     _ => {}
                 }
             }
@@ -497,3 +1150,106 @@ impl Broker for HyperliquidBroker {
 
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_update_statuses_map_to_events() {
+        let oid = OrderId(Uuid::new_v4());
+
+        assert!(matches!(order_update_event(oid, "open"), Some(OmsEvent::OrderAccepted { .. })));
+        assert!(order_update_event(oid, "filled").is_none());
+
+        for status in ["canceled", "marginCanceled", "selfTradeCanceled", "scheduledCancel"] {
+            assert!(
+                matches!(order_update_event(oid, status), Some(OmsEvent::OrderExpired { .. })),
+                "{}",
+                status
+            );
+        }
+
+        match order_update_event(oid, "badAloPxRejected") {
+            Some(OmsEvent::OrderRejected { reason, .. }) => assert_eq!(reason, RejectReason::PostOnlyWouldCross),
+            other => panic!("unexpected {:?}", other),
+        }
+        match order_update_event(oid, "tickRejected") {
+            Some(OmsEvent::OrderRejected { reason, .. }) => {
+                assert_eq!(reason, RejectReason::Unknown("tickRejected".into()))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(order_update_event(oid, "somethingNew").is_none());
+    }
+
+    #[test]
+    fn amend_sequence_does_not_cancel_the_order() {
+        let id = OrderId(Uuid::new_v4());
+        let mut modifying = HashMap::new();
+
+        // HL retires the old oid before the modify reply arrives
+        modifying.insert(id, None);
+        assert!(hold_cancel(&mut modifying, id, "canceled"));
+        assert!(!hold_cancel(&mut modifying, id, "open"));
+        assert!(resolve_modify(&mut modifying, id, true).is_none());
+
+        // later updates go through the oid check again
+        assert!(!hold_cancel(&mut modifying, id, "canceled"));
+
+        // a cancel held across a failed modify was the order's own
+        modifying.insert(id, None);
+        assert!(hold_cancel(&mut modifying, id, "marginCanceled"));
+        assert!(matches!(
+            resolve_modify(&mut modifying, id, false),
+            Some(OmsEvent::OrderExpired { order_id }) if order_id == id
+        ));
+        assert!(modifying.is_empty());
+
+        modifying.insert(id, None);
+        assert!(resolve_modify(&mut modifying, id, false).is_none());
+    }
+
+    #[test]
+    fn foreign_fills_map_to_adopted_orders() {
+        let ours = OrderId(Uuid::new_v4());
+        let cloid = format!("0x{}", ours.0.simple());
+        let oids = HashMap::from([(foreign_order_id(7), 7)]);
+
+        assert_eq!(ws_order_id(Some(&cloid), 1, &oids), Some(ours));
+        assert_eq!(ws_order_id(None, 7, &oids), Some(foreign_order_id(7)));
+        // never seen at startup: a flatten or a manual order
+        assert_eq!(ws_order_id(None, 8, &oids), None);
+    }
+
+    #[test]
+    fn foreign_order_ids_are_stable() {
+        assert_eq!(foreign_order_id(42), foreign_order_id(42));
+        assert_ne!(foreign_order_id(42), foreign_order_id(43));
+        assert_ne!(foreign_order_id(42).0.get_version_num(), 4);
+    }
+
+    #[test]
+    fn reject_text_maps_to_reason() {
+        assert_eq!(
+            parse_reject("Insufficient margin to place order. asset=3"),
+            RejectReason::InsufficientMargin
+        );
+        assert_eq!(
+            parse_reject("Post only order would have immediately matched, bbo was 97000@97001. asset=0"),
+            RejectReason::PostOnlyWouldCross
+        );
+        assert_eq!(
+            parse_reject("Order price cannot be more than 80% away from the reference price"),
+            RejectReason::PriceOutOfBand
+        );
+        assert_eq!(
+            parse_reject("Too many cumulative requests sent (10001) for cumulative volume traded $100."),
+            RejectReason::RateLimited
+        );
+        assert_eq!(
+            parse_reject("Order has invalid size."),
+            RejectReason::Unknown("Order has invalid size.".into())
+        );
+    }
+}
@@ -6,11 +6,22 @@ mod hyperliquid;
 pub use hyperliquid::HyperliquidBroker;
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use tokio::sync::mpsc;
 
-use crate::broker::types::BrokerCommand;
+use crate::broker::types::{BrokerCommand, VenueState};
+use crate::market::types::MarketEvent;
 
+#[async_trait]
 pub trait Broker: Send + Sync {
     fn command_sender(&self) -> mpsc::Sender<BrokerCommand>;
     fn start(self: Arc<Self>);
+
+    /// Open orders, positions and recent fills as the venue sees them.
+    /// Called once before `start`, so the OMS can reconcile before trading.
+    async fn venue_state(&self) -> anyhow::Result<VenueState>;
+
+    /// Market data as the OMS receives it. A real venue has its own book;
+    /// a simulated one needs ours to know where the touch is.
+    fn on_market(&self, _event: &MarketEvent) {}
 }
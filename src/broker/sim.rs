@@ -1,93 +1,344 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, Duration, Instant};
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-use crate::broker::{Broker, types::BrokerCommand};
+use async_trait::async_trait;
+
+use crate::broker::{Broker, types::{BrokerCommand, LimitOrder, VenueState}};
 use crate::oms::event::OmsEvent;
+use crate::oms::order::{CancelRejectReason, OrderId, RejectReason, Side, TimeInForce};
+use crate::oms::fill::{Fill, Liquidity};
+use crate::market::instrument::{Instrument, InstrumentRegistry};
+use crate::market::types::MarketEvent;
 
 use tracing::{info, warn, error};
 
 struct SimBrokerInner {
     cmd_rx: mpsc::Receiver<BrokerCommand>,
     oms_tx: mpsc::Sender<OmsEvent>,
+    /// Simulated venue position per symbol, for reduce-only checks
+    positions: HashMap<String, Decimal>,
+    /// Accepted orders with size left; they fill once the touch trades
+    /// through their price
+    resting: HashMap<OrderId, Resting>,
+    /// When the emulated dead-man's switch cancels everything, while armed
+    dead_man_at: Option<Instant>,
+    touch: Arc<std::sync::Mutex<HashMap<String, Touch>>>,
+}
+
+/// Best bid and ask last seen on the market feed
+#[derive(Debug, Clone, Copy, Default)]
+struct Touch {
+    bid: Option<Decimal>,
+    ask: Option<Decimal>,
+}
+
+/// What is left of an accepted order on the sim book
+struct Resting {
+    symbol: String,
+    side: Side,
+    qty: Decimal,
+    price: Decimal,
+    liquidity: Liquidity,
+}
+
+impl SimBrokerInner {
+    /// Venue-side cancel-all once the OMS stopped heartbeating
+    async fn fire_dead_man(&mut self) {
+        self.dead_man_at = None;
+        warn!("[SIM] no heartbeat, dead-man cancelling {} orders", self.resting.len());
+
+        for (order_id, _) in self.resting.drain() {
+            let _ = self.oms_tx.send(OmsEvent::OrderExpired { order_id }).await;
+        }
+    }
+
+    /// Fills the resting orders the touch has reached, at their own price
+    async fn fill_resting(&mut self) {
+        let touch = self.touch.lock().unwrap().clone();
+        let reached: Vec<OrderId> = self
+            .resting
+            .iter()
+            .filter(|(_, r)| {
+                let t = touch.get(&r.symbol).copied().unwrap_or_default();
+                match r.side {
+                    Side::Buy => t.ask.is_some_and(|ask| ask <= r.price),
+                    Side::Sell => t.bid.is_some_and(|bid| bid >= r.price),
+                }
+            })
+            .map(|(order_id, _)| *order_id)
+            .collect();
+
+        for order_id in reached {
+            let Some(r) = self.resting.remove(&order_id) else {
+                continue;
+            };
+            *self.positions.entry(r.symbol).or_insert(dec!(0)) += r.qty * r.side.sign();
+            let _ = self.oms_tx
+                .send(OmsEvent::Fill {
+                    fill: Fill::new(order_id, r.qty, r.price).with_liquidity(r.liquidity),
+                })
+                .await;
+        }
+    }
+
+    /// Accepts and fills one limit order the way the sim venue does
+    async fn place(&mut self, order: LimitOrder) {
+        let LimitOrder { order_id, symbol, side, qty, price, tif, flags } = order;
+
+        info!(
+            "[SIM] place {:?} {} qty={} @ {} {:?} {:?}",
+            order_id, symbol, qty, price, tif, flags
+        );
+
+        // post-only through the touch would take liquidity
+        if tif == TimeInForce::Alo {
+            let touch = self.touch.lock().unwrap().get(&symbol).copied().unwrap_or_default();
+            let crosses = match side {
+                Side::Buy => touch.ask.is_some_and(|ask| price >= ask),
+                Side::Sell => touch.bid.is_some_and(|bid| price <= bid),
+            };
+            if crosses {
+                sleep(Duration::from_millis(50)).await;
+                let _ = self.oms_tx
+                    .send(OmsEvent::OrderRejected { order_id, reason: RejectReason::PostOnlyWouldCross })
+                    .await;
+                return;
+            }
+        }
+
+        let position = self.positions.get(&symbol).copied().unwrap_or(dec!(0));
+
+        // reduce-only: trim to the position, reject if it would grow it
+        let fill_total = if flags.reduce_only {
+            if position * side.sign() >= dec!(0) {
+                sleep(Duration::from_millis(50)).await;
+                let _ = self.oms_tx
+                    .send(OmsEvent::OrderRejected {
+                        order_id,
+                        reason: RejectReason::Unknown("reduce only order would increase position".into()),
+                    })
+                    .await;
+                return;
+            }
+            qty.min(position.abs())
+        } else {
+            qty
+        };
+
+        sleep(Duration::from_millis(50)).await;
+        let _ = self.oms_tx
+            .send(OmsEvent::OrderAccepted { order_id })
+            .await;
+
+        let liquidity = match tif {
+            TimeInForce::Ioc => Liquidity::Taker,
+            TimeInForce::Gtc | TimeInForce::Alo => Liquidity::Maker,
+        };
+
+        // Gtc and Alo get hit in two chunks: the first now, the rest from
+        // the book once the touch reaches the price. IOC takes one chunk and
+        // dies; a trimmed reduce-only order trades out at once
+        let chunks: &[Decimal] = match tif {
+            TimeInForce::Ioc => &[dec!(0.4)],
+            _ if fill_total < qty => &[dec!(0.4), dec!(0.6)],
+            TimeInForce::Gtc | TimeInForce::Alo => &[dec!(0.4)],
+        };
+
+        let mut filled = dec!(0);
+        for chunk in chunks {
+            sleep(Duration::from_millis(50)).await;
+            let fill_qty = fill_total * chunk;
+            filled += fill_qty;
+            *self.positions.entry(symbol.clone()).or_insert(dec!(0)) +=
+                fill_qty * side.sign();
+            let _ = self.oms_tx
+                .send(OmsEvent::Fill {
+                    fill: Fill::new(order_id, fill_qty, price).with_liquidity(liquidity),
+                })
+            .await;
+        }
+
+        // IOC leftovers and trimmed reduce-only sizes never rest
+        if tif == TimeInForce::Ioc || fill_total < qty {
+            let _ = self.oms_tx
+                .send(OmsEvent::OrderExpired { order_id })
+                .await;
+            return;
+        }
+
+        self.resting.insert(order_id, Resting { symbol, side, qty: fill_total - filled, price, liquidity });
+    }
+
+    async fn cancel(&mut self, order_id: OrderId) {
+        let event = match self.resting.remove(&order_id) {
+            Some(_) => OmsEvent::CancelConfirmed { order_id },
+            // traded out or expired before the cancel got here
+            None => OmsEvent::CancelRejected { order_id, reason: CancelRejectReason::UnknownOrder },
+        };
+        sleep(Duration::from_millis(30)).await;
+        let _ = self.oms_tx.send(event).await;
+    }
 }
 
+/// Symbols the sim lists, with their size decimals
+const SIM_INSTRUMENTS: &[(&str, u32)] = &[("BTC", 5), ("ETH", 4), ("SOL", 2), ("TST", 2)];
+
+/// How often the sim checks its dead-man deadline
+const DEAD_MAN_CHECK: Duration = Duration::from_millis(100);
+
 pub struct SimBroker {
     cmd_tx: mpsc::Sender<BrokerCommand>,
     inner: Arc<Mutex<SimBrokerInner>>,
+    /// Shared with the inner loop, which holds its lock for good
+    touch: Arc<std::sync::Mutex<HashMap<String, Touch>>>,
 }
 
 impl SimBroker {
+    /// The sim's own listings; it never asks a real venue
+    pub fn instruments() -> InstrumentRegistry {
+        SIM_INSTRUMENTS
+            .iter()
+            .map(|(symbol, sz_decimals)| Instrument::perp(symbol, *sz_decimals))
+            .collect()
+    }
+
     pub fn new(
         cmd_rx: mpsc::Receiver<BrokerCommand>,
         cmd_tx: mpsc::Sender<BrokerCommand>,
         oms_tx: mpsc::Sender<OmsEvent>,
     ) -> Self {
+        let touch = Arc::new(std::sync::Mutex::new(HashMap::new()));
         Self {
             cmd_tx,
             inner: Arc::new(Mutex::new(SimBrokerInner {
                 cmd_rx,
                 oms_tx,
+                positions: HashMap::new(),
+                resting: HashMap::new(),
+                dead_man_at: None,
+                touch: touch.clone(),
             })),
+            touch,
         }
     }
 }
 
+#[async_trait]
 impl Broker for SimBroker {
+    async fn venue_state(&self) -> anyhow::Result<VenueState> {
+        // the sim venue starts empty every run
+        Ok(VenueState::default())
+    }
+
     fn command_sender(&self) -> mpsc::Sender<BrokerCommand> {
         self.cmd_tx.clone()
     }
 
+    fn on_market(&self, event: &MarketEvent) {
+        if let MarketEvent::Snapshot(snapshot) = event {
+            let touch = Touch {
+                bid: snapshot.book.bids.first().map(|l| l.price),
+                ask: snapshot.book.asks.first().map(|l| l.price),
+            };
+            self.touch.lock().unwrap().insert(snapshot.symbol.clone(), touch);
+        }
+    }
+
     fn start(self: Arc<Self>) {
         let inner = self.inner.clone();
 
         tokio::spawn(async move {
             let mut inner = inner.lock().await;
+            let mut check = tokio::time::interval(DEAD_MAN_CHECK);
+
+            loop {
+                let next = tokio::select! {
+                    cmd = inner.cmd_rx.recv() => Some(cmd),
+                    _ = check.tick() => None,
+                };
+                let cmd = match next {
+                    Some(Some(cmd)) => cmd,
+                    // OMS gone: nothing left to report cancels to
+                    Some(None) => break,
+                    None => {
+                        // the switch fires before the book trades
+                        if inner.dead_man_at.is_some_and(|at| Instant::now() >= at) {
+                            inner.fire_dead_man().await;
+                        }
+                        inner.fill_resting().await;
+                        continue;
+                    }
+                };
 
-            while let Some(cmd) = inner.cmd_rx.recv().await {
                 match cmd {
                     BrokerCommand::PlaceLimit {
                         order_id,
+                        symbol,
+                        side,
                         qty,
                         price,
-                        ..
+                        tif,
+                        flags,
                     } => {
-                        info!("[SIM] place {:?} qty={} @ {}", order_id, qty, price);
-
-                        sleep(Duration::from_millis(50)).await;
-                        let _ = inner.oms_tx
-                            .send(OmsEvent::OrderAccepted { order_id })
+                        inner
+                            .place(LimitOrder { order_id, symbol, side, qty, price, tif, flags })
                             .await;
+                    }
 
-                        sleep(Duration::from_millis(50)).await;
-                        let _ = inner.oms_tx
-                            .send(OmsEvent::Fill {
-                                order_id,
-                                qty: qty * dec!(0.4),
-                                price,
-                            })
-                        .await;
-
-                        sleep(Duration::from_millis(50)).await;
-                        let _ = inner.oms_tx
-                            .send(OmsEvent::Fill {
-                                order_id,
-                                qty: qty * dec!(0.6),
-                                price,
-                            })
-                        .await;
+                    BrokerCommand::PlaceBatch { orders } => {
+                        info!("[SIM] batch of {} orders", orders.len());
+                        for order in orders {
+                            inner.place(order).await;
+                        }
+                    }
+
+                    BrokerCommand::Modify { order_id, qty, price, .. } => {
+                        info!("[SIM] modify {:?} qty={} @ {}", order_id, qty, price);
+                        if let Some(r) = inner.resting.get_mut(&order_id) {
+                            r.qty = qty;
+                            r.price = price;
                         }
 
-                    BrokerCommand::Cancel { order_id } => {
                         sleep(Duration::from_millis(30)).await;
                         let _ = inner.oms_tx
-                            .send(OmsEvent::CancelConfirmed { order_id })
+                            .send(OmsEvent::AmendAccepted { order_id })
                             .await;
                         }
 
-                    BrokerCommand::Flatten { qty, limit_px, }=> {
-                        info!("qty {} {}", qty, limit_px);
+                    BrokerCommand::Cancel { order_id, .. } => {
+                        inner.cancel(order_id).await;
+                    }
+
+                    BrokerCommand::CancelBatch { orders } => {
+                        for (order_id, _) in orders {
+                            inner.cancel(order_id).await;
+                        }
+                    }
+
+                    BrokerCommand::QueryStatus { order_id, .. } => {
+                        let event = if inner.resting.contains_key(&order_id) {
+                            OmsEvent::OrderAccepted { order_id }
+                        } else {
+                            // every sim order is acked, so this only follows a restart
+                            warn!("[SIM] no record of {:?}", order_id);
+                            OmsEvent::OrderNotFound { order_id }
+                        };
+                        let _ = inner.oms_tx.send(event).await;
+                    }
+
+                    BrokerCommand::Heartbeat { timeout } => {
+                        if inner.dead_man_at.is_none() {
+                            info!("[SIM] dead-man armed, cancel-all after {:?} of silence", timeout);
+                        }
+                        inner.dead_man_at = Some(Instant::now() + timeout);
+                    }
+
+                    BrokerCommand::Flatten { symbol, qty, limit_px, }=> {
+                        info!("{} qty {} {}", symbol, qty, limit_px);
                         return;
                     }
                 }
@@ -95,3 +346,129 @@ impl Broker for SimBroker {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::types::{BookLevel, MarketSnapshot, OrderBook};
+    use crate::oms::config::OmsConfig;
+    use crate::oms::order::OrderFlags;
+
+    fn post_only(side: Side, price: Decimal) -> BrokerCommand {
+        BrokerCommand::PlaceLimit {
+            order_id: OrderId(uuid::Uuid::new_v4()),
+            symbol: "TST".into(),
+            side,
+            qty: dec!(1),
+            price,
+            tif: TimeInForce::Alo,
+            flags: OrderFlags::default(),
+        }
+    }
+
+    fn gtc_buy(order_id: OrderId, price: Decimal) -> BrokerCommand {
+        BrokerCommand::PlaceLimit {
+            order_id,
+            symbol: "TST".into(),
+            side: Side::Buy,
+            qty: dec!(1),
+            price,
+            tif: TimeInForce::Gtc,
+            flags: OrderFlags::default(),
+        }
+    }
+
+    fn book(bid: Decimal, ask: Decimal) -> MarketEvent {
+        MarketEvent::Snapshot(MarketSnapshot {
+            symbol: "TST".into(),
+            book: OrderBook {
+                bids: vec![BookLevel { price: bid, qty: dec!(1) }],
+                asks: vec![BookLevel { price: ask, qty: dec!(1) }],
+            },
+            timestamp_ms: 0,
+        })
+    }
+
+    #[tokio::test]
+    async fn post_only_through_the_touch_is_rejected() {
+        let (cmd_tx, cmd_rx) = mpsc::channel(8);
+        let (oms_tx, mut oms_rx) = mpsc::channel(8);
+        let sim = Arc::new(SimBroker::new(cmd_rx, cmd_tx.clone(), oms_tx));
+        sim.on_market(&book(dec!(99), dec!(101)));
+        sim.clone().start();
+
+        for (side, price) in [(Side::Buy, dec!(101)), (Side::Sell, dec!(98))] {
+            cmd_tx.send(post_only(side, price)).await.unwrap();
+            match oms_rx.recv().await {
+                Some(OmsEvent::OrderRejected { reason, .. }) => assert_eq!(reason, RejectReason::PostOnlyWouldCross),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        // behind the touch it rests as usual
+        cmd_tx.send(post_only(Side::Buy, dec!(100))).await.unwrap();
+        assert!(matches!(oms_rx.recv().await, Some(OmsEvent::OrderAccepted { .. })));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resting_order_fills_once_the_touch_reaches_it() {
+        let (cmd_tx, cmd_rx) = mpsc::channel(8);
+        let (oms_tx, mut oms_rx) = mpsc::channel(8);
+        let sim = Arc::new(SimBroker::new(cmd_rx, cmd_tx.clone(), oms_tx));
+        sim.on_market(&book(dec!(99), dec!(101)));
+        sim.clone().start();
+
+        let order_id = OrderId(uuid::Uuid::new_v4());
+        cmd_tx.send(gtc_buy(order_id, dec!(100))).await.unwrap();
+        assert!(matches!(oms_rx.recv().await, Some(OmsEvent::OrderAccepted { .. })));
+        assert!(matches!(oms_rx.recv().await, Some(OmsEvent::Fill { fill }) if fill.qty == dec!(0.4)));
+
+        // many checks with the ask above the bid: the rest stays on the book
+        sleep(Duration::from_secs(5)).await;
+        assert!(oms_rx.try_recv().is_err());
+
+        sim.on_market(&book(dec!(99), dec!(100)));
+        match oms_rx.recv().await {
+            Some(OmsEvent::Fill { fill }) => {
+                assert_eq!(fill.qty, dec!(0.6));
+                assert_eq!(fill.price, dec!(100));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_oms_gets_its_orders_expired() {
+        let (cmd_tx, cmd_rx) = mpsc::channel(8);
+        let (oms_tx, mut oms_rx) = mpsc::channel(8);
+        let sim = Arc::new(SimBroker::new(cmd_rx, cmd_tx.clone(), oms_tx));
+        sim.on_market(&book(dec!(99), dec!(101)));
+        sim.clone().start();
+
+        // one heartbeat at the default timeout, then nothing
+        let timeout = OmsConfig::default().dead_man_timeout.unwrap();
+        let armed = Instant::now();
+        cmd_tx.send(BrokerCommand::Heartbeat { timeout }).await.unwrap();
+        let order_id = OrderId(uuid::Uuid::new_v4());
+        cmd_tx.send(gtc_buy(order_id, dec!(100))).await.unwrap();
+
+        let mut filled = dec!(0);
+        loop {
+            let event = tokio::time::timeout(timeout * 2, oms_rx.recv())
+                .await
+                .expect("dead-man never fired")
+                .unwrap();
+            match event {
+                OmsEvent::OrderAccepted { .. } => {}
+                OmsEvent::Fill { fill } => filled += fill.qty,
+                OmsEvent::OrderExpired { order_id: expired } => {
+                    assert_eq!(expired, order_id);
+                    assert!(armed.elapsed() >= timeout);
+                    break;
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert_eq!(filled, dec!(0.4));
+    }
+}
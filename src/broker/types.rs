@@ -1,22 +1,116 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use rust_decimal::Decimal;
-use crate::oms::order::{OrderId, Side};
+use crate::oms::order::{OrderId, Side, TimeInForce, OrderFlags};
 
 #[derive(Debug, Clone)]
 pub enum BrokerCommand {
     PlaceLimit {
         order_id: OrderId,
+        symbol: String,
+        side: Side,
+        qty: Decimal,
+        price: Decimal,
+        tif: TimeInForce,
+        flags: OrderFlags,
+    },
+
+    /// Cancel-replace a resting order, keeping its client order id
+    Modify {
+        order_id: OrderId,
+        symbol: String,
         side: Side,
         qty: Decimal,
         price: Decimal,
+        tif: TimeInForce,
+        flags: OrderFlags,
     },
 
     Cancel {
         order_id: OrderId,
+        symbol: String,
+    },
+
+    /// Several limit orders in one venue request; results come back per order
+    PlaceBatch {
+        orders: Vec<LimitOrder>,
+    },
+
+    /// Several cancels in one venue request, as `(order_id, symbol)`
+    CancelBatch {
+        orders: Vec<(OrderId, String)>,
+    },
+
+    /// Look an order up by cloid and report what the venue has
+    QueryStatus {
+        order_id: OrderId,
+        symbol: String,
+    },
+
+    /// Dead-man's switch: the venue cancels every order unless another
+    /// heartbeat arrives within `timeout`
+    Heartbeat {
+        timeout: Duration,
     },
 
     /// Market order to flatten position
     Flatten {
+        symbol: String,
         qty: Decimal,
         limit_px: Decimal,
     },
 }
+
+/// One order of a `PlaceBatch`, as `PlaceLimit` carries it
+#[derive(Debug, Clone)]
+pub struct LimitOrder {
+    pub order_id: OrderId,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: Decimal,
+    pub price: Decimal,
+    pub tif: TimeInForce,
+    pub flags: OrderFlags,
+}
+
+/// A resting order as the venue reports it. Orders placed without a cloid
+/// get an `order_id` the broker derives from the exchange's own id, so it
+/// is the same on every restart.
+#[derive(Debug, Clone)]
+pub struct VenueOrder {
+    pub order_id: OrderId,
+    pub symbol: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub original_qty: Decimal,
+    pub remaining_qty: Decimal,
+    pub tif: TimeInForce,
+    pub flags: OrderFlags,
+}
+
+#[derive(Debug, Clone)]
+pub struct VenuePosition {
+    /// Signed
+    pub net_qty: Decimal,
+    pub entry_price: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct VenueFill {
+    /// `None` if the fill belongs to an order that is not ours
+    pub order_id: Option<OrderId>,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: Decimal,
+    pub price: Decimal,
+}
+
+/// Everything the venue knows about the account, fetched once at startup
+#[derive(Debug, Clone, Default)]
+pub struct VenueState {
+    pub open_orders: Vec<VenueOrder>,
+    pub positions: HashMap<String, VenuePosition>,
+    /// Recent fills, newest last
+    pub fills: Vec<VenueFill>,
+}
@@ -1,7 +1,7 @@
 use rust_decimal_macros::dec;
 use tokio::time::{sleep, Duration};
 
-use laminar::oms::event::OmsEvent;
+use laminar::oms::config::OmsConfig;
 use laminar::oms::runtime::start_oms;
 use laminar::strategy::mm::run_mm_strategy;
 use laminar::rms::driver::start_rms_driver;
@@ -10,6 +10,9 @@ use laminar::market::hyperliquid::HyperliquidMarket;
 use laminar::market::MarketAdapter;
 use tracing::{info, warn, error};
 
+/// Perps we quote
+const SYMBOLS: &[&str] = &["TST"];
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     info!("[MAIN] starting laminar");
@@ -20,34 +23,45 @@ async fn main() -> anyhow::Result<()> {
         .with_ansi(false)
         .init();
 
-    let oms = start_oms().await;
-    let tx = oms.sender();
+    let config = OmsConfig::default();
+    let oms = start_oms(config.clone()).await;
+    let handle = oms.handle();
+    let instruments = oms.instruments();
 
     // set an initial target
-    tx.send(OmsEvent::SetTarget { qty: dec!(0) })
-        .await
-        .unwrap();
+    for symbol in SYMBOLS {
+        handle.set_target(symbol, dec!(0), None, None).await?;
+    }
 
-    let market = HyperliquidMarket::new("TST").await?;
+    let market = HyperliquidMarket::new(SYMBOLS, config.network).await?;
     market.start();
 
-    let market_rx = market.subscribe();
+    oms.feed_market(market.subscribe());
+
     let market_rx_tui = market.subscribe();
     let market_rx_rms= market.subscribe();
 
-    // start strategy loop
-    tokio::spawn(run_mm_strategy(market_rx, tx.clone()));
+    // start one strategy loop per symbol
+    for symbol in SYMBOLS {
+        let Some(instrument) = instruments.get(symbol).cloned() else {
+            error!("[MAIN] {} is not listed on {:?}, not quoting it", symbol, config.network);
+            continue;
+        };
+        let market_rx = market.subscribe();
+        tokio::spawn(run_mm_strategy(symbol.to_string(), market_rx, handle.clone(), instrument));
+    }
 
     tokio::spawn(
         start_rms_driver(
             market_rx_rms,
-            tx.clone(),
+            oms.subscribe_anomalies(),
+            handle.clone(),
             Duration::from_secs(5),
         )
     );
 
     // run tui loop
-    tokio::spawn(laminar::tui::run::run_tui(tx.clone(), market_rx_tui));
+    tokio::spawn(laminar::tui::run::run_tui(handle.clone(), market_rx_tui, instruments.clone()));
 
     // let it run
     tokio::signal::ctrl_c().await?;
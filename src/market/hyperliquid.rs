@@ -7,6 +7,8 @@ use rust_decimal::Decimal;
 use rust_decimal::prelude::FromStr;
 
 use hyperliquid_rust_sdk::{
+    AssetCtx,
+    BaseUrl,
     InfoClient,
     Subscription,
     Message,
@@ -19,20 +21,46 @@ use crate::market::types::{
     BookLevel,
     Trade,
     AggressorSide,
+    MarkPrice,
 };
+use crate::market::instrument::{Instrument, InstrumentRegistry, Network};
 use crate::market::MarketAdapter;
 
+pub fn base_url(network: Network) -> BaseUrl {
+    match network {
+        Network::Mainnet => BaseUrl::Mainnet,
+        Network::Testnet => BaseUrl::Testnet,
+    }
+}
+
+/// Perp universe of `network` as an `InstrumentRegistry`
+pub async fn load_instruments(network: Network) -> anyhow::Result<InstrumentRegistry> {
+    let info = InfoClient::new(None, Some(base_url(network))).await?;
+    let meta = info.meta().await?;
+
+    let registry: InstrumentRegistry = meta
+        .universe
+        .iter()
+        .map(|asset| Instrument::perp(&asset.name, asset.sz_decimals))
+        .collect();
+
+    info!("[MARKET][HL] loaded {} {:?} instruments", registry.len(), network);
+    Ok(registry)
+}
+
 pub struct HyperliquidMarket {
-    symbol: String,
+    symbols: Vec<String>,
+    network: Network,
     tx: broadcast::Sender<MarketEvent>,
 }
 
 impl HyperliquidMarket {
-    pub async fn new(symbol: &str) -> anyhow::Result<Self> {
+    pub async fn new(symbols: &[&str], network: Network) -> anyhow::Result<Self> {
         let (tx, _) = broadcast::channel(4096);
 
         Ok(Self {
-            symbol: symbol.to_string(),
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            network,
             tx,
         })
     }
@@ -44,33 +72,44 @@ impl MarketAdapter for HyperliquidMarket {
     }
 
     fn start(&self) {
-        let symbol = self.symbol.clone();
+        let symbols = self.symbols.clone();
+        let base_url = base_url(self.network);
         let tx = self.tx.clone();
 
         tokio::spawn(async move {
-            let mut info = InfoClient::with_reconnect(None, None)
+            let mut info = InfoClient::with_reconnect(None, Some(base_url))
                 .await
                 .expect("failed to create InfoClient");
 
             let (msg_tx, mut msg_rx) = mpsc::unbounded_channel();
 
-            // ---- L2 book ----
-            info.subscribe(
-                Subscription::L2Book { coin: symbol.clone() },
-                msg_tx.clone(),
-            )
-            .await
-            .expect("failed to subscribe to L2Book");
+            for symbol in &symbols {
+                // ---- L2 book ----
+                info.subscribe(
+                    Subscription::L2Book { coin: symbol.clone() },
+                    msg_tx.clone(),
+                )
+                .await
+                .expect("failed to subscribe to L2Book");
 
-            // ---- Trades (tape) ----
-            info.subscribe(
-                Subscription::Trades { coin: symbol.clone() },
-                msg_tx,
-            )
-            .await
-            .expect("failed to subscribe to Trades");
+                // ---- Trades (tape) ----
+                info.subscribe(
+                    Subscription::Trades { coin: symbol.clone() },
+                    msg_tx.clone(),
+                )
+                .await
+                .expect("failed to subscribe to Trades");
 
-            info!("HL market subscribed (L2 + Trades) for {}", symbol);
+                // ---- Mark price ----
+                info.subscribe(
+                    Subscription::ActiveAssetCtx { coin: symbol.clone() },
+                    msg_tx.clone(),
+                )
+                .await
+                .expect("failed to subscribe to ActiveAssetCtx");
+
+                info!("HL market subscribed (L2 + Trades + Mark) for {}", symbol);
+            }
 
             while let Some(msg) = msg_rx.recv().await {
                 match msg {
@@ -97,7 +136,7 @@ impl MarketAdapter for HyperliquidMarket {
                             .collect::<Vec<_>>();
 
                         let snapshot = MarketSnapshot {
-                            symbol: symbol.clone(),
+                            symbol: book.data.coin.clone(),
                             book: OrderBook { bids, asks },
                             timestamp_ms: book.data.time,
                         };
@@ -126,7 +165,7 @@ impl MarketAdapter for HyperliquidMarket {
                             };
 
                             let trade = Trade {
-                                symbol: symbol.clone(),
+                                symbol: t.coin.clone(),
                                 price,
                                 qty,
                                 side,
@@ -137,6 +176,26 @@ impl MarketAdapter for HyperliquidMarket {
                         }
                     }
 
+                    // -------- MARK --------
+                    Message::ActiveAssetCtx(ctx) => {
+                        let shared = match &ctx.data.ctx {
+                            AssetCtx::Perps(p) => &p.shared,
+                            AssetCtx::Spot(s) => &s.shared,
+                        };
+                        let price = match Decimal::from_str(&shared.mark_px) {
+                            Ok(p) => p,
+                            Err(_) => continue,
+                        };
+
+                        let mark = MarkPrice {
+                            symbol: ctx.data.coin.clone(),
+                            price,
+                            timestamp_ms: chrono::Utc::now().timestamp_millis() as u64,
+                        };
+
+                        let _ = tx.send(MarketEvent::Mark(mark));
+                    }
+
                     Message::NoData => {
                         warn!("HL stream returned NoData");
                     }
@@ -149,7 +208,7 @@ impl MarketAdapter for HyperliquidMarket {
                 }
             }
 
-            warn!("HL market stream exited for {}", symbol);
+            warn!("HL market stream exited for {:?}", symbols);
         });
     }
 }
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+use crate::oms::order::Side;
+
+/// Which venue deployment to trade and load metadata from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+/// Significant figures a price may carry unless it is an integer
+const PRICE_SIG_FIGS: i32 = 5;
+
+/// Decimal places a perp price may carry before `sz_decimals` is taken off
+const PERP_PRICE_DECIMALS: u32 = 6;
+
+/// Smallest order value the venue accepts, in quote currency
+pub const DEFAULT_MIN_NOTIONAL: Decimal = dec!(10);
+
+/// Trading rules of one symbol, as the venue publishes them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instrument {
+    pub symbol: String,
+    /// Decimal places of a size; the lot is 10^-sz_decimals
+    pub sz_decimals: u32,
+    /// Most decimal places a price may have
+    pub max_price_decimals: u32,
+    pub min_notional: Decimal,
+}
+
+impl Instrument {
+    pub fn perp(symbol: &str, sz_decimals: u32) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            sz_decimals,
+            max_price_decimals: PERP_PRICE_DECIMALS.saturating_sub(sz_decimals),
+            min_notional: DEFAULT_MIN_NOTIONAL,
+        }
+    }
+
+    pub fn lot_size(&self) -> Decimal {
+        Decimal::new(1, self.sz_decimals)
+    }
+
+    /// Price increment valid around `price`: five significant figures,
+    /// capped by `max_price_decimals`. Integers are always valid.
+    pub fn tick_at(&self, price: Decimal) -> Decimal {
+        let price = price.abs();
+        if price == dec!(0) {
+            return Decimal::new(1, self.max_price_decimals);
+        }
+
+        let mut magnitude = 0;
+        let mut x = price;
+        while x >= dec!(10) {
+            x /= dec!(10);
+            magnitude += 1;
+        }
+        while x < dec!(1) {
+            x *= dec!(10);
+            magnitude -= 1;
+        }
+
+        let exp = (magnitude - (PRICE_SIG_FIGS - 1)).clamp(-(self.max_price_decimals as i32), 0);
+        Decimal::new(1, exp.unsigned_abs())
+    }
+
+    /// Nearest valid price on the passive side: bids down, asks up
+    pub fn round_price(&self, price: Decimal, side: Side) -> Decimal {
+        let tick = self.tick_at(price);
+        let steps = match side {
+            Side::Buy => (price / tick).floor(),
+            Side::Sell => (price / tick).ceil(),
+        };
+        (steps * tick).normalize()
+    }
+
+    /// Nearest valid price towards zero, for prices with no side to favour
+    pub fn truncate_price(&self, price: Decimal) -> Decimal {
+        let tick = self.tick_at(price);
+        ((price / tick).trunc() * tick).normalize()
+    }
+
+    /// Size rounded down to whole lots
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        qty.round_dp_with_strategy(self.sz_decimals, RoundingStrategy::ToZero)
+    }
+
+    pub fn meets_min_notional(&self, qty: Decimal, price: Decimal) -> bool {
+        qty.abs() * price >= self.min_notional
+    }
+}
+
+/// Every tradable symbol's `Instrument`, loaded once and shared by
+/// strategies, the OMS and the broker
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, Instrument>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, instrument: Instrument) {
+        self.instruments.insert(instrument.symbol.clone(), instrument);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(symbol)
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.instruments.contains_key(symbol)
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+}
+
+impl FromIterator<Instrument> for InstrumentRegistry {
+    fn from_iter<I: IntoIterator<Item = Instrument>>(iter: I) -> Self {
+        let mut registry = Self::new();
+        for instrument in iter {
+            registry.insert(instrument);
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_follows_sig_figs_and_decimal_cap() {
+        let btc = Instrument::perp("BTC", 5);
+        assert_eq!(btc.tick_at(dec!(97123.4)), dec!(1));
+        assert_eq!(btc.tick_at(dec!(123456)), dec!(1));
+        assert_eq!(btc.tick_at(dec!(9712.3)), dec!(0.1));
+
+        // sz_decimals 2 leaves 4 price decimals, below sig figs here
+        let small = Instrument::perp("SMALL", 2);
+        assert_eq!(small.tick_at(dec!(0.012345)), dec!(0.0001));
+        assert_eq!(small.tick_at(dec!(1.2345)), dec!(0.0001));
+        assert_eq!(small.tick_at(dec!(12.345)), dec!(0.001));
+    }
+
+    #[test]
+    fn prices_round_to_the_passive_side_and_sizes_down() {
+        let eth = Instrument::perp("ETH", 4);
+        assert_eq!(eth.round_price(dec!(3456.789), Side::Buy), dec!(3456.7));
+        assert_eq!(eth.round_price(dec!(3456.789), Side::Sell), dec!(3456.8));
+        assert_eq!(eth.truncate_price(dec!(3456.789)), dec!(3456.7));
+
+        assert_eq!(eth.round_qty(dec!(0.123456)), dec!(0.1234));
+        assert_eq!(eth.lot_size(), dec!(0.0001));
+
+        assert!(eth.meets_min_notional(dec!(0.003), dec!(3456)));
+        assert!(!eth.meets_min_notional(dec!(0.002), dec!(3456)));
+    }
+}
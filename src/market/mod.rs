@@ -1,4 +1,5 @@
 pub mod types;
+pub mod instrument;
 
 #[cfg(feature = "hyperliquid")]
 pub mod hyperliquid;
@@ -39,9 +39,18 @@ pub struct Trade {
     pub timestamp_ms: u64,
 }
 
+/// Venue mark price
+#[derive(Debug, Clone)]
+pub struct MarkPrice {
+    pub symbol: String,
+    pub price: Decimal,
+    pub timestamp_ms: u64,
+}
+
 /// Unified market stream
 #[derive(Debug, Clone)]
 pub enum MarketEvent {
     Snapshot(MarketSnapshot),
     Trade(Trade),
+    Mark(MarkPrice),
 }
@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 
 #[derive(Debug, Clone)]
 pub struct AccountSnapshot {
@@ -7,5 +10,18 @@ pub struct AccountSnapshot {
     pub used_margin: Decimal,
     pub unrealized_pnl: Decimal,
     pub realized_pnl: Decimal,
-    pub net_position: Decimal,
+    /// Signed venue position per symbol
+    pub positions: HashMap<String, Decimal>,
+    /// Venue average entry price per open position
+    pub entry_prices: HashMap<String, Decimal>,
+}
+
+impl AccountSnapshot {
+    pub fn net_position(&self, symbol: &str) -> Decimal {
+        self.positions.get(symbol).copied().unwrap_or(dec!(0))
+    }
+
+    pub fn entry_price(&self, symbol: &str) -> Option<Decimal> {
+        self.entry_prices.get(symbol).copied()
+    }
 }
@@ -0,0 +1,587 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+use super::engine::OmsEngine;
+use super::order::{Order, OrderId, Side, TimeInForce};
+use crate::market::types::Trade;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlgoId(pub Uuid);
+
+/// Shortest gap between two IOC children of one parent
+const MIN_CHILD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest gap an IOC parent backs off to while its children go unfilled
+const MAX_CHILD_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long a finished child is remembered, for fills reported after the
+/// venue closed it
+const LATE_FILL_GRACE: Duration = Duration::from_secs(30);
+
+/// How a parent order is sliced into children
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlgoKind {
+    /// Even slices over `duration`, one every `duration / slices`
+    Twap { duration: Duration, slices: u32 },
+
+    /// Keep our fills at `participation` of the tape printed since start
+    Vwap { participation: Decimal },
+
+    /// One resting child of `display_qty` at a time
+    Iceberg { display_qty: Decimal },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoState {
+    Running,
+    /// No new children; working ones are cancelled
+    Paused,
+    Cancelled,
+    Done,
+}
+
+/// What a parent knows about one of its children
+#[derive(Debug, Clone)]
+struct Child {
+    filled: Decimal,
+    /// When the child was first seen finished
+    ended_at: Option<Instant>,
+}
+
+/// A parent order and its progress
+#[derive(Debug, Clone)]
+pub struct ParentOrder {
+    pub id: AlgoId,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: Decimal,
+    /// Worst price any child may trade at
+    pub limit_price: Decimal,
+    pub kind: AlgoKind,
+    pub state: AlgoState,
+    pub filled: Decimal,
+    notional: Decimal,
+    /// Tape volume seen since start, for VWAP
+    tape_volume: Decimal,
+    started_at: Instant,
+    /// Time spent paused, so TWAP does not rush to catch up on resume
+    paused_for: Duration,
+    paused_at: Option<Instant>,
+    children: HashMap<OrderId, Child>,
+    /// When the last IOC child went out, and how long until the next may
+    last_child_at: Option<Instant>,
+    backoff: Duration,
+}
+
+impl ParentOrder {
+    pub fn avg_price(&self) -> Decimal {
+        if self.filled == dec!(0) {
+            dec!(0)
+        } else {
+            self.notional / self.filled
+        }
+    }
+
+    pub fn view(&self) -> AlgoView {
+        AlgoView {
+            id: self.id,
+            symbol: self.symbol.clone(),
+            side: self.side,
+            kind: self.kind.clone(),
+            state: self.state,
+            qty: self.qty,
+            filled: self.filled,
+            avg_price: self.avg_price(),
+        }
+    }
+
+    /// Unfilled size of children still live on the venue
+    fn working(&self, engine: &OmsEngine) -> Decimal {
+        self.children
+            .keys()
+            .filter_map(|id| engine.order(*id))
+            .map(|o| o.remaining_signed_qty().abs())
+            .sum()
+    }
+
+    /// How much should have been sent by `now`, fills plus working
+    fn scheduled(&self, now: Instant) -> Decimal {
+        match &self.kind {
+            AlgoKind::Twap { duration, slices } => {
+                let slices = (*slices).max(1);
+                let active = now.duration_since(self.started_at).saturating_sub(self.paused_for);
+                let slice_len = *duration / slices;
+                let done = if slice_len.is_zero() {
+                    slices
+                } else {
+                    // slice 0 goes out immediately
+                    ((active.as_millis() / slice_len.as_millis().max(1)) as u32 + 1).min(slices)
+                };
+                self.qty * Decimal::from(done) / Decimal::from(slices)
+            }
+            AlgoKind::Vwap { participation } => (self.tape_volume * participation).min(self.qty),
+            AlgoKind::Iceberg { .. } => self.qty,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlgoView {
+    pub id: AlgoId,
+    pub symbol: String,
+    pub side: Side,
+    pub kind: AlgoKind,
+    pub state: AlgoState,
+    pub qty: Decimal,
+    pub filled: Decimal,
+    pub avg_price: Decimal,
+}
+
+/// What the algo engine wants the OMS to do
+#[derive(Debug, Clone)]
+pub enum AlgoAction {
+    Place { order: Box<Order> },
+    Cancel { order_id: OrderId },
+}
+
+/// Owns parent orders and slices them into child `Order`s.
+///
+/// Parents live in memory only, and are dropped once done or cancelled and
+/// their last child is forgotten. Their children are ordinary journaled
+/// orders, so after a restart they show up as plain orders without a parent.
+#[derive(Debug, Default)]
+pub struct AlgoEngine {
+    parents: HashMap<AlgoId, ParentOrder>,
+    parent_of: HashMap<OrderId, AlgoId>,
+}
+
+impl AlgoEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        qty: Decimal,
+        limit_price: Decimal,
+        kind: AlgoKind,
+        now: Instant,
+    ) -> AlgoId {
+        let id = AlgoId(Uuid::new_v4());
+        self.parents.insert(
+            id,
+            ParentOrder {
+                id,
+                symbol: symbol.to_string(),
+                side,
+                qty,
+                limit_price,
+                kind,
+                state: AlgoState::Running,
+                filled: dec!(0),
+                notional: dec!(0),
+                tape_volume: dec!(0),
+                started_at: now,
+                paused_for: Duration::ZERO,
+                paused_at: None,
+                children: HashMap::new(),
+                last_child_at: None,
+                backoff: MIN_CHILD_INTERVAL,
+            },
+        );
+        id
+    }
+
+    pub fn parent(&self, id: AlgoId) -> Option<&ParentOrder> {
+        self.parents.get(&id)
+    }
+
+    pub fn views(&self) -> Vec<AlgoView> {
+        self.parents.values().map(|p| p.view()).collect()
+    }
+
+    /// Cancels for the parent's live children
+    fn cancel_children(&self, id: AlgoId, engine: &OmsEngine) -> Vec<AlgoAction> {
+        let Some(parent) = self.parents.get(&id) else {
+            return Vec::new();
+        };
+        let live: Vec<OrderId> = engine
+            .open_orders(Some(&parent.symbol))
+            .into_iter()
+            .map(|(oid, _)| oid)
+            .collect();
+
+        parent
+            .children
+            .keys()
+            .filter(|oid| live.contains(oid))
+            .map(|oid| AlgoAction::Cancel { order_id: *oid })
+            .collect()
+    }
+
+    pub fn pause(&mut self, id: AlgoId, engine: &OmsEngine, now: Instant) -> Vec<AlgoAction> {
+        match self.parents.get_mut(&id) {
+            Some(p) if p.state == AlgoState::Running => {
+                p.state = AlgoState::Paused;
+                p.paused_at = Some(now);
+            }
+            _ => return Vec::new(),
+        }
+        self.cancel_children(id, engine)
+    }
+
+    pub fn resume(&mut self, id: AlgoId, now: Instant) {
+        if let Some(p) = self.parents.get_mut(&id) {
+            if p.state == AlgoState::Paused {
+                p.state = AlgoState::Running;
+                if let Some(at) = p.paused_at.take() {
+                    p.paused_for += now.duration_since(at);
+                }
+            }
+        }
+    }
+
+    pub fn cancel(&mut self, id: AlgoId, engine: &OmsEngine) -> Vec<AlgoAction> {
+        match self.parents.get_mut(&id) {
+            Some(p) if matches!(p.state, AlgoState::Running | AlgoState::Paused) => {
+                p.state = AlgoState::Cancelled;
+            }
+            _ => return Vec::new(),
+        }
+        self.cancel_children(id, engine)
+    }
+
+    /// Books a child fill against its parent. Returns the parent, if any.
+    pub fn on_fill(&mut self, order_id: OrderId, qty: Decimal, price: Decimal) -> Option<AlgoId> {
+        let id = *self.parent_of.get(&order_id)?;
+        let parent = self.parents.get_mut(&id)?;
+
+        parent.filled += qty;
+        parent.notional += qty * price;
+        if let Some(child) = parent.children.get_mut(&order_id) {
+            child.filled += qty;
+        }
+        // the price is reachable again
+        parent.backoff = MIN_CHILD_INTERVAL;
+        if parent.filled >= parent.qty {
+            parent.state = AlgoState::Done;
+        }
+        Some(id)
+    }
+
+    pub fn on_trade(&mut self, trade: &Trade) {
+        for p in self.parents.values_mut() {
+            if p.symbol == trade.symbol && p.state == AlgoState::Running {
+                p.tape_volume += trade.qty;
+            }
+        }
+    }
+
+    /// Notes children that have finished, backing the parent off when one
+    /// went without a fill, and forgets them once late fills are unlikely.
+    /// A finished parent goes with its last child.
+    fn prune(&mut self, engine: &OmsEngine, now: Instant) {
+        for parent in self.parents.values_mut() {
+            for (id, child) in parent.children.iter_mut() {
+                let done = engine.order(*id).is_none_or(|o| o.is_terminal());
+                if !done || child.ended_at.is_some() {
+                    continue;
+                }
+                child.ended_at = Some(now);
+                if child.filled == dec!(0) {
+                    parent.backoff = (parent.backoff * 2).min(MAX_CHILD_BACKOFF);
+                }
+            }
+
+            parent.children.retain(|id, child| {
+                let keep = child.ended_at.is_none_or(|at| now.duration_since(at) < LATE_FILL_GRACE);
+                if !keep {
+                    self.parent_of.remove(id);
+                }
+                keep
+            });
+        }
+
+        self.parents.retain(|_, p| {
+            matches!(p.state, AlgoState::Running | AlgoState::Paused) || !p.children.is_empty()
+        });
+    }
+
+    /// New children for every running parent that is behind schedule
+    pub fn plan(&mut self, engine: &OmsEngine, now: Instant) -> Vec<AlgoAction> {
+        self.prune(engine, now);
+        let mut actions = Vec::new();
+
+        for parent in self.parents.values_mut() {
+            if parent.state != AlgoState::Running {
+                continue;
+            }
+
+            let working = parent.working(engine);
+            let left = parent.qty - parent.filled - working;
+            if left <= dec!(0) {
+                continue;
+            }
+
+            // what is left is under one lot or the minimum notional: the
+            // venue would refuse it, so the parent is as done as it gets
+            let price = parent.limit_price;
+            if engine.tradable_qty(&parent.symbol, left, price) == dec!(0) {
+                if working == dec!(0) {
+                    parent.state = AlgoState::Done;
+                }
+                continue;
+            }
+
+            let (qty, tif) = match &parent.kind {
+                // only ever one slice of the iceberg showing
+                AlgoKind::Iceberg { display_qty } => {
+                    if working > dec!(0) {
+                        continue;
+                    }
+                    (left.min(*display_qty), TimeInForce::Gtc)
+                }
+                // schedule-driven children take what is there and go, but
+                // not faster than the parent's back-off allows
+                AlgoKind::Twap { .. } | AlgoKind::Vwap { .. } => {
+                    if parent.last_child_at.is_some_and(|at| now.duration_since(at) < parent.backoff) {
+                        continue;
+                    }
+                    let behind = parent.scheduled(now) - parent.filled - working;
+                    if behind <= dec!(0) {
+                        continue;
+                    }
+                    (behind.min(left), TimeInForce::Ioc)
+                }
+            };
+
+            // a slice too small to send waits until the schedule adds to it
+            let qty = engine.tradable_qty(&parent.symbol, qty, price);
+            if qty == dec!(0) {
+                continue;
+            }
+
+            let order = Order::new(&parent.symbol, parent.side, qty, price).with_tif(tif);
+            if tif == TimeInForce::Ioc {
+                parent.last_child_at = Some(now);
+            }
+            parent.children.insert(order.id, Child { filled: dec!(0), ended_at: None });
+            self.parent_of.insert(order.id, parent.id);
+            actions.push(AlgoAction::Place { order: Box::new(order) });
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::instrument::Instrument;
+    use crate::market::types::AggressorSide;
+    use crate::oms::fill::Fill;
+
+    fn placed(actions: &[AlgoAction]) -> Vec<Decimal> {
+        actions
+            .iter()
+            .filter_map(|a| match a {
+                AlgoAction::Place { order } => Some(order.original_qty),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Books every placed child into the engine and fills it completely
+    fn fill_all(engine: &mut OmsEngine, algos: &mut AlgoEngine, actions: Vec<AlgoAction>, price: Decimal) {
+        for a in actions {
+            if let AlgoAction::Place { order } = a {
+                let (id, qty) = (order.id, order.original_qty);
+                engine.insert_order(*order);
+                engine.on_order_accepted(id).unwrap();
+                engine.on_fill(&Fill::new(id, qty, price)).unwrap();
+                algos.on_fill(id, qty, price);
+            }
+        }
+    }
+
+    #[test]
+    fn twap_follows_schedule_and_tracks_average() {
+        let mut engine = OmsEngine::new();
+        let mut algos = AlgoEngine::new();
+        let t0 = Instant::now();
+
+        let id = algos.start(
+            "TST",
+            Side::Buy,
+            dec!(4),
+            dec!(110),
+            AlgoKind::Twap { duration: Duration::from_secs(40), slices: 4 },
+            t0,
+        );
+
+        let first = algos.plan(&engine, t0);
+        assert_eq!(placed(&first), vec![dec!(1)]);
+        fill_all(&mut engine, &mut algos, first, dec!(100));
+
+        // nothing new until the next slice is due
+        assert!(algos.plan(&engine, t0 + Duration::from_secs(5)).is_empty());
+
+        let second = algos.plan(&engine, t0 + Duration::from_secs(10));
+        assert_eq!(placed(&second), vec![dec!(1)]);
+        fill_all(&mut engine, &mut algos, second, dec!(102));
+
+        let parent = algos.parent(id).unwrap();
+        assert_eq!(parent.filled, dec!(2));
+        assert_eq!(parent.avg_price(), dec!(101));
+
+        // catches up on everything still owed at the end
+        let rest = algos.plan(&engine, t0 + Duration::from_secs(45));
+        assert_eq!(placed(&rest), vec![dec!(2)]);
+        fill_all(&mut engine, &mut algos, rest, dec!(101));
+        assert_eq!(algos.parent(id).unwrap().state, AlgoState::Done);
+    }
+
+    #[test]
+    fn untradable_remainder_completes_the_parent() {
+        let mut engine = OmsEngine::new();
+        engine.set_instruments(std::sync::Arc::new([Instrument::perp("TST", 2)].into_iter().collect()));
+        let mut algos = AlgoEngine::new();
+        let t0 = Instant::now();
+
+        let id = algos.start(
+            "TST",
+            Side::Buy,
+            dec!(1.005),
+            dec!(100),
+            AlgoKind::Twap { duration: Duration::from_secs(40), slices: 2 },
+            t0,
+        );
+
+        // half of 1.005 rounds down to whole lots
+        let first = algos.plan(&engine, t0);
+        assert_eq!(placed(&first), vec![dec!(0.50)]);
+        fill_all(&mut engine, &mut algos, first, dec!(100));
+
+        let second = algos.plan(&engine, t0 + Duration::from_secs(20));
+        assert_eq!(placed(&second), vec![dec!(0.50)]);
+        fill_all(&mut engine, &mut algos, second, dec!(100));
+
+        // 0.005 left: under one lot, nothing sent and the parent is done
+        assert!(algos.plan(&engine, t0 + Duration::from_secs(40)).is_empty());
+        assert_eq!(algos.parent(id).unwrap().state, AlgoState::Done);
+    }
+
+    #[test]
+    fn unfilled_ioc_backs_off_and_finished_children_are_forgotten() {
+        let mut engine = OmsEngine::new();
+        let mut algos = AlgoEngine::new();
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        algos.start("TST", Side::Buy, dec!(4), dec!(100), AlgoKind::Twap { duration: Duration::ZERO, slices: 1 }, t0);
+
+        let expire = |engine: &mut OmsEngine, actions: Vec<AlgoAction>| {
+            for a in actions {
+                if let AlgoAction::Place { order } = a {
+                    let id = order.id;
+                    engine.insert_order(*order);
+                    engine.on_order_accepted(id).unwrap();
+                    engine.on_order_expired(id).unwrap();
+                }
+            }
+        };
+
+        let first = algos.plan(&engine, t0);
+        assert_eq!(placed(&first), vec![dec!(4)]);
+        expire(&mut engine, first);
+
+        // not before the minimum interval; at it, the miss doubles the wait
+        assert!(algos.plan(&engine, at(500)).is_empty());
+        assert!(algos.plan(&engine, at(1_000)).is_empty());
+        let second = algos.plan(&engine, at(2_000));
+        assert_eq!(placed(&second), vec![dec!(4)]);
+        expire(&mut engine, second);
+
+        // seen finished now, forgotten once the grace has passed
+        assert!(algos.plan(&engine, at(3_000)).is_empty());
+        assert_eq!(algos.parent_of.len(), 2);
+        let third = algos.plan(&engine, at(3_000) + LATE_FILL_GRACE);
+        assert_eq!(placed(&third), vec![dec!(4)]);
+        assert_eq!(algos.parent_of.len(), 1);
+    }
+
+    #[test]
+    fn finished_parents_are_dropped_with_their_last_child() {
+        let mut engine = OmsEngine::new();
+        let mut algos = AlgoEngine::new();
+        let t0 = Instant::now();
+
+        let done = algos.start("TST", Side::Buy, dec!(1), dec!(100), AlgoKind::Iceberg { display_qty: dec!(1) }, t0);
+        let first = algos.plan(&engine, t0);
+        fill_all(&mut engine, &mut algos, first, dec!(100));
+        let cancelled = algos.start("TST", Side::Sell, dec!(1), dec!(100), AlgoKind::Vwap { participation: dec!(0.1) }, t0);
+        algos.cancel(cancelled, &engine);
+
+        // the filled child is still remembered for late fills
+        algos.plan(&engine, t0);
+        assert_eq!(algos.parent(done).unwrap().state, AlgoState::Done);
+        assert!(algos.parent(cancelled).is_none());
+
+        algos.plan(&engine, t0 + LATE_FILL_GRACE);
+        assert!(algos.parent(done).is_none());
+        assert!(algos.views().is_empty());
+    }
+
+    #[test]
+    fn vwap_participates_in_tape() {
+        let engine = OmsEngine::new();
+        let mut algos = AlgoEngine::new();
+        let t0 = Instant::now();
+
+        algos.start("TST", Side::Sell, dec!(10), dec!(90), AlgoKind::Vwap { participation: dec!(0.1) }, t0);
+        assert!(algos.plan(&engine, t0).is_empty());
+
+        algos.on_trade(&Trade {
+            symbol: "TST".into(),
+            price: dec!(95),
+            qty: dec!(30),
+            side: AggressorSide::Sell,
+            timestamp_ms: 0,
+        });
+        assert_eq!(placed(&algos.plan(&engine, t0)), vec![dec!(3)]);
+    }
+
+    #[test]
+    fn iceberg_shows_one_slice_and_pauses() {
+        let mut engine = OmsEngine::new();
+        let mut algos = AlgoEngine::new();
+        let t0 = Instant::now();
+
+        let id = algos.start("TST", Side::Buy, dec!(5), dec!(100), AlgoKind::Iceberg { display_qty: dec!(2) }, t0);
+
+        let actions = algos.plan(&engine, t0);
+        assert_eq!(placed(&actions), vec![dec!(2)]);
+        let AlgoAction::Place { order } = &actions[0] else { unreachable!() };
+        let child = order.id;
+        engine.insert_order(order.as_ref().clone());
+        engine.on_order_accepted(child).unwrap();
+
+        // slice still showing → nothing more
+        assert!(algos.plan(&engine, t0).is_empty());
+
+        let cancels = algos.pause(id, &engine, t0);
+        assert!(matches!(cancels.as_slice(), [AlgoAction::Cancel { order_id }] if *order_id == child));
+        assert!(algos.plan(&engine, t0).is_empty());
+
+        algos.resume(id, t0);
+        engine.request_cancel(child).unwrap();
+        engine.on_cancel_confirmed(child).unwrap();
+        assert_eq!(placed(&algos.plan(&engine, t0)), vec![dec!(2)]);
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::drift::DriftConfig;
+use crate::market::instrument::Network;
+use super::mark::MarkSource;
+use super::position::CostBasis;
+use super::reconcile::ForeignOrderPolicy;
+
+#[derive(Debug, Clone)]
+pub struct OmsConfig {
+    /// Venue deployment to trade on and load instruments from
+    pub network: Network,
+
+    /// Directory holding the event journal and its compacted snapshot
+    pub journal_dir: PathBuf,
+
+    /// Fold the journal into a snapshot after this many entries
+    pub compact_every: usize,
+
+    /// An order not acked within this long is marked Unknown and its
+    /// status queried; Unknown orders are re-queried at the same interval
+    pub ack_timeout: Duration,
+
+    /// How often the OMS checks its timers
+    pub tick_interval: Duration,
+
+    /// Resting orders found on the venue at startup that are not ours
+    pub foreign_orders: ForeignOrderPolicy,
+
+    /// Checks of OMS positions against polled venue positions
+    pub drift: DriftConfig,
+
+    /// Price positions are marked against for unrealized PnL
+    pub mark_source: MarkSource,
+
+    /// Average entry price, or FIFO/LIFO lots with per-lot realized PnL
+    pub cost_basis: CostBasis,
+
+    /// Venue cancels every order if the OMS loop goes this long without a
+    /// heartbeat (hang or lost connection). `None` disables the switch.
+    pub dead_man_timeout: Option<Duration>,
+}
+
+impl Default for OmsConfig {
+    fn default() -> Self {
+        Self {
+            network: Network::default(),
+            journal_dir: PathBuf::from("journal"),
+            compact_every: 10_000,
+            ack_timeout: Duration::from_secs(5),
+            tick_interval: Duration::from_secs(1),
+            foreign_orders: ForeignOrderPolicy::default(),
+            drift: DriftConfig::default(),
+            mark_source: MarkSource::default(),
+            cost_basis: CostBasis::default(),
+            dead_man_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
@@ -28,8 +30,9 @@ impl Sub for Quantity {
 
 #[derive(Debug)]
 pub struct OmsCore {
-    /// What the strategy wants
-    target_position: Quantity,
+    /// What each strategy wants; `None` is the shared, unnamed target.
+    /// The venue works towards their sum.
+    targets: HashMap<Option<String>, Quantity>,
 
     /// What has actually been filled
     filled_position: Quantity,
@@ -41,7 +44,7 @@ pub struct OmsCore {
 impl OmsCore {
     pub fn new() -> Self {
         Self {
-            target_position: Quantity::zero(),
+            targets: HashMap::new(),
             filled_position: Quantity::zero(),
             open_orders: Vec::new(),
         }
@@ -49,7 +52,33 @@ impl OmsCore {
 
     /// Strategy intent
     pub fn set_target_position(&mut self, qty: Quantity) {
-        self.target_position = qty;
+        self.set_strategy_target(None, qty);
+    }
+
+    pub fn set_strategy_target(&mut self, strategy: Option<&str>, qty: Quantity) {
+        self.targets.insert(strategy.map(str::to_string), qty);
+    }
+
+    /// Netted over every strategy
+    pub fn target_position(&self) -> Quantity {
+        self.targets
+            .values()
+            .copied()
+            .fold(Quantity::zero(), |acc, q| acc + q)
+    }
+
+    pub fn strategy_target(&self, strategy: Option<&str>) -> Quantity {
+        self.targets
+            .get(&strategy.map(str::to_string))
+            .copied()
+            .unwrap_or(Quantity::zero())
+    }
+
+    /// Named strategies and their targets
+    pub fn strategy_targets(&self) -> impl Iterator<Item = (&str, Quantity)> {
+        self.targets
+            .iter()
+            .filter_map(|(s, q)| Some((s.as_deref()?, *q)))
     }
 
     /// Exchange truth
@@ -57,6 +86,11 @@ impl OmsCore {
         self.filled_position = self.filled_position + fill_qty;
     }
 
+    /// Replace filled truth wholesale, e.g. with the venue position
+    pub fn set_filled_position(&mut self, qty: Quantity) {
+        self.filled_position = qty;
+    }
+
     /// OMS bookkeeping
     pub fn add_open_order(&mut self, qty: Quantity) {
         self.open_orders.push(qty);
@@ -80,7 +114,7 @@ impl OmsCore {
     ///
     /// delta = target - (filled + open) to enforce the invariant i.e T = P + O
     pub fn delta(&self) -> Quantity {
-        self.target_position - (self.filled_position + self.open_exposure())
+        self.target_position() - (self.filled_position + self.open_exposure())
     }
 
     pub fn clear_open_orders(&mut self) {
@@ -126,4 +160,21 @@ mod tests {
         assert_eq!(oms.delta().0, dec!(-1.5));
 
     }
+
+    #[test]
+    fn strategy_targets_are_netted() {
+        let mut oms = OmsCore::new();
+
+        oms.set_strategy_target(Some("a"), Quantity(dec!(2.0)));
+        oms.set_strategy_target(Some("b"), Quantity(dec!(-1.5)));
+        assert_eq!(oms.delta().0, dec!(0.5));
+
+        // the unnamed target nets in too
+        oms.set_target_position(Quantity(dec!(-0.5)));
+        assert_eq!(oms.delta().0, dec!(0.0));
+
+        oms.set_strategy_target(Some("b"), Quantity(dec!(0)));
+        assert_eq!(oms.strategy_target(Some("a")).0, dec!(2.0));
+        assert_eq!(oms.delta().0, dec!(1.5));
+    }
 }
@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::account::AccountSnapshot;
+use super::engine::OmsEngine;
+
+/// What to do once drift between our position and the venue's persists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DriftAction {
+    /// Book a synthetic fill for the difference so we match the venue
+    Correct,
+
+    /// Stop trading and leave it to a human
+    #[default]
+    Halt,
+}
+
+#[derive(Debug, Clone)]
+pub struct DriftConfig {
+    /// Differences up to this size are ignored
+    pub tolerance: Decimal,
+
+    /// Drift must show up in this many consecutive account snapshots before
+    /// we act. Fills in flight on the WS close the gap within one or two.
+    pub persist_for: u32,
+
+    pub action: DriftAction,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: dec!(0),
+            persist_for: 3,
+            action: DriftAction::default(),
+        }
+    }
+}
+
+/// A position mismatch that outlived the grace period
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    pub symbol: String,
+    pub ours: Decimal,
+    pub venue: Decimal,
+}
+
+impl Drift {
+    /// Signed quantity that takes our position to the venue's
+    pub fn correction(&self) -> Decimal {
+        self.venue - self.ours
+    }
+}
+
+/// Compares OMS positions with each polled account snapshot
+#[derive(Debug)]
+pub struct PositionReconciler {
+    cfg: DriftConfig,
+    /// Consecutive snapshots each symbol has been out of tolerance
+    strikes: HashMap<String, u32>,
+}
+
+impl PositionReconciler {
+    pub fn new(cfg: DriftConfig) -> Self {
+        Self {
+            cfg,
+            strikes: HashMap::new(),
+        }
+    }
+
+    pub fn action(&self) -> DriftAction {
+        self.cfg.action
+    }
+
+    /// Returns the symbols whose drift has persisted long enough to act on.
+    /// Their counters reset, so each returned drift is acted on once.
+    pub fn check(&mut self, engine: &OmsEngine, acct: &AccountSnapshot) -> Vec<Drift> {
+        let symbols: HashSet<String> = engine
+            .symbols()
+            .into_iter()
+            .chain(acct.positions.keys().cloned())
+            .collect();
+
+        let mut persistent = Vec::new();
+
+        for symbol in symbols {
+            let ours = engine.position(&symbol).map_or(dec!(0), |p| p.net_qty);
+            let venue = acct.net_position(&symbol);
+
+            if (venue - ours).abs() <= self.cfg.tolerance {
+                self.strikes.remove(&symbol);
+                continue;
+            }
+
+            let strikes = self.strikes.entry(symbol.clone()).or_insert(0);
+            *strikes += 1;
+
+            if *strikes >= self.cfg.persist_for {
+                self.strikes.remove(&symbol);
+                persistent.push(Drift { symbol, ours, venue });
+            }
+        }
+
+        persistent.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        persistent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oms::order::Side;
+    use crate::oms::fill::Fill;
+
+    fn account(positions: &[(&str, Decimal)]) -> AccountSnapshot {
+        AccountSnapshot {
+            equity: dec!(1000),
+            available_margin: dec!(1000),
+            used_margin: dec!(0),
+            unrealized_pnl: dec!(0),
+            realized_pnl: dec!(0),
+            positions: positions.iter().map(|(s, q)| (s.to_string(), *q)).collect(),
+            entry_prices: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn transient_drift_is_tolerated() {
+        let mut engine = OmsEngine::new();
+        let oid = engine.create_order("TST", Side::Buy, dec!(1), dec!(100));
+        engine.on_order_accepted(oid).unwrap();
+
+        let mut rec = PositionReconciler::new(DriftConfig::default());
+
+        // venue saw the fill before our WS did
+        assert!(rec.check(&engine, &account(&[("TST", dec!(1))])).is_empty());
+        engine.on_fill(&Fill::new(oid, dec!(1), dec!(100))).unwrap();
+        assert!(rec.check(&engine, &account(&[("TST", dec!(1))])).is_empty());
+
+        // counter was reset, so two more strikes are still fine
+        assert!(rec.check(&engine, &account(&[("TST", dec!(2))])).is_empty());
+        assert!(rec.check(&engine, &account(&[("TST", dec!(2))])).is_empty());
+    }
+
+    #[test]
+    fn persistent_drift_is_reported_once() {
+        let engine = OmsEngine::new();
+        let mut rec = PositionReconciler::new(DriftConfig {
+            tolerance: dec!(0.001),
+            persist_for: 2,
+            action: DriftAction::Correct,
+        });
+
+        let acct = account(&[("TST", dec!(-0.5)), ("DUST", dec!(0.0005))]);
+        assert!(rec.check(&engine, &acct).is_empty());
+
+        let drift = rec.check(&engine, &acct);
+        assert_eq!(
+            drift,
+            vec![Drift { symbol: "TST".into(), ours: dec!(0), venue: dec!(-0.5) }]
+        );
+        assert_eq!(drift[0].correction(), dec!(-0.5));
+
+        assert!(rec.check(&engine, &acct).is_empty());
+    }
+}
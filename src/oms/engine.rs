@@ -1,46 +1,417 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use rust_decimal::Decimal;
+use rust_decimal::prelude::Signed;
 use rust_decimal_macros::dec;
 
 use super::core::{OmsCore, Quantity};
-use super::position::Position;
-use super::order::{Order, OrderId, Side};
+use super::position::{ClosedLot, CostBasis, Position};
+use super::error::OrderError;
+use super::fill::Fill;
+use super::order::{CancelRejectReason, Order, OrderId, RejectReason, Side};
 use crate::oms::account::AccountSnapshot;
+use crate::oms::journal::{EngineSnapshot, JournalEntry};
 use crate::oms::state::TradingState;
+use crate::market::instrument::{Instrument, InstrumentRegistry};
 
 #[derive(Debug)]
 pub struct OmsEngine {
-    core: OmsCore,
+    cores: HashMap<String, OmsCore>,
     orders: HashMap<OrderId, Order>,
-    position: Position,
+    positions: HashMap<String, Position>,
+    /// Slice of `positions` traded by tagged orders, tag → symbol
+    tag_positions: HashMap<String, HashMap<String, Position>>,
+    /// Latest mark per symbol; not journaled, the feed refills it
+    marks: HashMap<String, Decimal>,
+    /// Accounting mode for every position
+    cost_basis: CostBasis,
     account: Option<AccountSnapshot>,
     trading_state: TradingState,
+    /// Venue trading rules; not journaled, loaded at startup
+    instruments: Arc<InstrumentRegistry>,
 }
 
 impl OmsEngine {
     pub fn new() -> Self {
         Self {
-            core: OmsCore::new(),
+            cores: HashMap::new(),
             orders: HashMap::new(),
-            position: Position::new(),
+            positions: HashMap::new(),
+            tag_positions: HashMap::new(),
+            marks: HashMap::new(),
+            cost_basis: CostBasis::default(),
             account: None,
             trading_state: TradingState::Running,
+            instruments: Arc::default(),
+        }
+    }
+
+    /// Rebuilds an engine from a compacted journal snapshot
+    pub fn from_snapshot(snapshot: EngineSnapshot) -> Self {
+        let mut engine = Self::new();
+
+        for (symbol, qty) in snapshot.targets {
+            engine.set_target_position(&symbol, qty);
+        }
+
+        for (symbol, targets) in snapshot.strategy_targets {
+            for (strategy, qty) in targets {
+                engine.set_strategy_target(&symbol, Some(&strategy), qty);
+            }
+        }
+
+        for (symbol, position) in snapshot.positions {
+            // filled truth is exactly the net of all fills
+            engine.core_mut(&symbol).on_fill(Quantity(position.net_qty));
+            engine.positions.insert(symbol, position);
+        }
+
+        for order in snapshot.orders {
+            engine.core_mut(&order.symbol);
+            engine.orders.insert(order.id, order);
+        }
+
+        engine.tag_positions = snapshot.tag_positions;
+        engine.trading_state = snapshot.trading_state;
+        engine.recompute_open_exposure();
+        engine
+    }
+
+    pub fn to_snapshot(&self, last_seq: u64) -> EngineSnapshot {
+        EngineSnapshot {
+            last_seq,
+            orders: self.orders.values().cloned().collect(),
+            targets: self
+                .cores
+                .iter()
+                .map(|(symbol, core)| (symbol.clone(), core.strategy_target(None).0))
+                .collect(),
+            strategy_targets: self
+                .cores
+                .iter()
+                .map(|(symbol, core)| {
+                    let targets = core.strategy_targets().map(|(s, q)| (s.to_string(), q.0)).collect();
+                    (symbol.clone(), targets)
+                })
+                .collect(),
+            positions: self.positions.clone(),
+            tag_positions: self.tag_positions.clone(),
+            trading_state: self.trading_state,
+        }
+    }
+
+    /// Single entry point for journaled state changes, used both live and
+    /// when replaying the journal on startup.
+    pub fn apply(&mut self, entry: &JournalEntry) -> Result<(), OrderError> {
+        match entry {
+            JournalEntry::SetTarget { symbol, qty, strategy } => {
+                self.set_strategy_target(symbol, strategy.as_deref(), *qty);
+                Ok(())
+            }
+            JournalEntry::InternalCross { symbol, price } => {
+                self.cross_internal(symbol, *price);
+                Ok(())
+            }
+            JournalEntry::OrderCreated { order } => {
+                self.insert_order(order.clone());
+                Ok(())
+            }
+            JournalEntry::OrderSent { order_id } => {
+                self.on_order_sent(*order_id)
+            }
+            JournalEntry::StatusUnknown { order_id } => {
+                self.on_status_unknown(*order_id)
+            }
+            JournalEntry::OrderAccepted { order_id } => {
+                self.on_order_accepted(*order_id)
+            }
+            JournalEntry::OrderRejected { order_id, reason } => {
+                self.on_order_rejected(*order_id, reason.clone())
+            }
+            JournalEntry::Fill { fill } => {
+                self.on_fill(fill)
+            }
+            JournalEntry::AmendRequested { order_id, price, qty } => {
+                self.request_amend(*order_id, *price, *qty)
+            }
+            JournalEntry::AmendAccepted { order_id } => {
+                self.on_amend_accepted(*order_id)
+            }
+            JournalEntry::AmendRejected { order_id } => {
+                self.on_amend_rejected(*order_id)
+            }
+            JournalEntry::CancelRequested { order_id } => {
+                self.request_cancel(*order_id)
+            }
+            JournalEntry::CancelConfirmed { order_id } => {
+                self.on_cancel_confirmed(*order_id)
+            }
+            JournalEntry::CancelRejected { order_id, reason } => {
+                self.on_cancel_rejected(*order_id, reason)
+            }
+            JournalEntry::OrderExpired { order_id } => {
+                self.on_order_expired(*order_id)
+            }
+            JournalEntry::TradingState { state, .. } => {
+                self.set_trading_state(*state);
+                Ok(())
+            }
+            JournalEntry::OrderSynced { order } => {
+                self.insert_order(order.clone());
+                self.recompute_open_exposure();
+                Ok(())
+            }
+            JournalEntry::PositionSynced { symbol, net_qty, avg_price } => {
+                self.sync_position(symbol, *net_qty, *avg_price);
+                Ok(())
+            }
+            JournalEntry::AdjustmentFill { symbol, qty, price } => {
+                self.on_adjustment_fill(symbol, *qty, *price);
+                Ok(())
+            }
         }
     }
 
     /* ---------- Strategy/tui-facing ---------- */
 
-    pub fn set_target_position(&mut self, qty: Decimal) {
-        self.core.set_target_position(Quantity(qty));
+    pub fn set_target_position(&mut self, symbol: &str, qty: Decimal) {
+        self.core_mut(symbol).set_target_position(Quantity(qty));
+    }
+
+    /// One strategy's target; `None` is the shared, unnamed one. The venue
+    /// delta works towards the sum over all strategies.
+    pub fn set_strategy_target(&mut self, symbol: &str, strategy: Option<&str>, qty: Decimal) {
+        self.core_mut(symbol).set_strategy_target(strategy, Quantity(qty));
+    }
+
+    /// What each named strategy still needs on `symbol`: its target less
+    /// what has been allocated to it. Sorted by strategy, zeros left out.
+    pub fn strategy_wants(&self, symbol: &str) -> Vec<(String, Decimal)> {
+        let Some(core) = self.cores.get(symbol) else {
+            return Vec::new();
+        };
+        let mut wants: Vec<(String, Decimal)> = core
+            .strategy_targets()
+            .map(|(strategy, target)| {
+                let held = self.tag_position(strategy, symbol).map_or(dec!(0), |p| p.net_qty);
+                (strategy.to_string(), target.0 - held)
+            })
+            .filter(|(_, want)| *want != dec!(0))
+            .collect();
+        wants.sort_by(|a, b| a.0.cmp(&b.0));
+        wants
+    }
+
+    /// Size opposing strategies could trade with each other instead of
+    /// both going to the venue
+    pub fn crossable(&self, symbol: &str) -> Decimal {
+        let wants = self.strategy_wants(symbol);
+        let buys: Decimal = wants.iter().map(|(_, w)| (*w).max(dec!(0))).sum();
+        let sells: Decimal = wants.iter().map(|(_, w)| (-w).max(dec!(0))).sum();
+        buys.min(sells)
+    }
+
+    /// Crosses opposing strategy wants with each other at `price`. Only
+    /// allocations move; the venue position is untouched. Returns the size
+    /// crossed.
+    pub fn cross_internal(&mut self, symbol: &str, price: Decimal) -> Decimal {
+        let qty = self.crossable(symbol);
+        if qty == dec!(0) {
+            return qty;
+        }
+
+        let wants = self.strategy_wants(symbol);
+        for sign in [dec!(1), dec!(-1)] {
+            let side: Vec<(String, Decimal)> = wants
+                .iter()
+                .filter(|(_, w)| w.signum() == sign)
+                .map(|(s, w)| (s.clone(), w.abs()))
+                .collect();
+            for (strategy, share) in pro_rata(&side, qty) {
+                self.tag_position_mut(&strategy, symbol).apply_fill(share * sign, price);
+            }
+        }
+        qty
+    }
+
+    pub fn delta(&self, symbol: &str) -> Decimal {
+        self.cores
+            .get(symbol)
+            .map(|c| c.delta().0)
+            .unwrap_or(dec!(0))
+    }
+
+    pub fn position(&self, symbol: &str) -> Option<&Position> {
+        self.positions.get(symbol)
+    }
+
+    /// Account-level lots closed by the last entry applied, by symbol. Tag
+    /// positions close the same quantity and are not reported twice.
+    pub fn take_closed_lots(&mut self) -> Vec<(String, ClosedLot)> {
+        let mut lots = Vec::new();
+        for (symbol, pos) in self.positions.iter_mut() {
+            lots.extend(pos.closed_lots.drain(..).map(|lot| (symbol.clone(), lot)));
+        }
+        lots
+    }
+
+    /// Applies to open positions too; see `Position::set_cost_basis`
+    pub fn set_cost_basis(&mut self, cost_basis: CostBasis) {
+        self.cost_basis = cost_basis;
+        for pos in self.positions.values_mut().chain(self.tag_positions.values_mut().flat_map(|p| p.values_mut())) {
+            pos.set_cost_basis(cost_basis);
+        }
+    }
+
+    pub fn set_mark(&mut self, symbol: &str, price: Decimal) {
+        self.marks.insert(symbol.to_string(), price);
+    }
+
+    pub fn mark(&self, symbol: &str) -> Option<Decimal> {
+        self.marks.get(symbol).copied()
     }
 
-    pub fn delta(&self) -> Decimal {
-        self.core.delta().0
+    pub fn set_instruments(&mut self, instruments: Arc<InstrumentRegistry>) {
+        self.instruments = instruments;
     }
 
-    pub fn position(&self) -> &Position {
-        &self.position
+    pub fn instrument(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(symbol)
+    }
+
+    /// Puts `order` on its instrument's grid (bids down, asks up, size down
+    /// to whole lots) and checks what is left against the venue's rules
+    pub fn validate(&self, order: &mut Order) -> Result<(), RejectReason> {
+        let (price, qty) = self.quantize(&order.symbol, order.side, order.limit_price, order.original_qty)?;
+        order.limit_price = price;
+        order.original_qty = qty;
+        Ok(())
+    }
+
+    /// `price` and `qty` on the instrument's grid, or the rule they break
+    pub fn quantize(
+        &self,
+        symbol: &str,
+        side: Side,
+        price: Decimal,
+        qty: Decimal,
+    ) -> Result<(Decimal, Decimal), RejectReason> {
+        let instrument = self.instrument(symbol).ok_or(RejectReason::UnknownSymbol)?;
+        if price <= dec!(0) {
+            return Err(RejectReason::NonPositivePrice);
+        }
+
+        let price = instrument.round_price(price, side);
+        let rounded = instrument.round_qty(qty);
+
+        if rounded <= dec!(0) {
+            return Err(RejectReason::ZeroQty { requested: qty });
+        }
+        if !instrument.meets_min_notional(rounded, price) {
+            return Err(RejectReason::BelowMinNotional {
+                notional: rounded * price,
+                min: instrument.min_notional,
+            });
+        }
+        Ok((price, rounded))
+    }
+
+    /// Largest part of `qty` the venue would take at `price`: whole lots,
+    /// or zero when that is under the minimum notional. Planners size
+    /// children with it so remainders never turn into local rejects.
+    /// Unchanged for symbols without an instrument.
+    pub fn tradable_qty(&self, symbol: &str, qty: Decimal, price: Decimal) -> Decimal {
+        let Some(instrument) = self.instrument(symbol) else {
+            return qty;
+        };
+        let qty = instrument.round_qty(qty);
+        if qty > dec!(0) && instrument.meets_min_notional(qty, price) {
+            qty
+        } else {
+            dec!(0)
+        }
+    }
+
+    /// Every symbol the OMS has seen a target, order or fill for
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.cores.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    pub fn position_views(&self) -> Vec<super::snapshot::PositionView> {
+        self.symbols()
+            .into_iter()
+            .map(|symbol| self.position_view(&symbol))
+            .collect()
+    }
+
+    pub fn position_view(&self, symbol: &str) -> super::snapshot::PositionView {
+        // borrowed: closed lots are not worth copying for a view
+        let flat;
+        let pos = match self.positions.get(symbol) {
+            Some(pos) => pos,
+            None => {
+                flat = Position::new();
+                &flat
+            }
+        };
+        let mark = self.mark(symbol);
+        // unmarked positions count as flat on price, not as zero-priced
+        let unrealized_pnl = mark.map_or(dec!(0), |m| pos.unrealized_pnl(m));
+        super::snapshot::PositionView {
+            delta: self.delta(symbol),
+            mark,
+            unrealized_pnl,
+            total_pnl: pos.net_pnl() + unrealized_pnl,
+            notional: mark.map_or(dec!(0), |m| pos.notional(m)),
+            symbol: symbol.to_string(),
+            net_qty: pos.net_qty,
+            avg_price: pos.avg_price,
+            realized_pnl: pos.realized_pnl,
+            fees: pos.fees,
+            net_pnl: pos.net_pnl(),
+            maker_volume: pos.maker_volume,
+            taker_volume: pos.taker_volume,
+        }
+    }
+
+    pub fn tag_position(&self, tag: &str, symbol: &str) -> Option<&Position> {
+        self.tag_positions.get(tag)?.get(symbol)
+    }
+
+    fn tag_position_mut(&mut self, tag: &str, symbol: &str) -> &mut Position {
+        let cost_basis = self.cost_basis;
+        self.tag_positions
+            .entry(tag.to_string())
+            .or_default()
+            .entry(symbol.to_string())
+            .or_insert_with(|| Position::with_cost_basis(cost_basis))
+    }
+
+    pub fn tag_views(&self) -> Vec<super::snapshot::TagPositionView> {
+        let mut views: Vec<_> = self
+            .tag_positions
+            .iter()
+            .flat_map(|(tag, positions)| {
+                positions.iter().map(move |(symbol, pos)| super::snapshot::TagPositionView {
+                    tag: tag.clone(),
+                    symbol: symbol.clone(),
+                    target: self.cores.get(symbol).and_then(|c| {
+                        c.strategy_targets().find(|(s, _)| s == tag).map(|(_, q)| q.0)
+                    }),
+                    net_qty: pos.net_qty,
+                    avg_price: pos.avg_price,
+                    realized_pnl: pos.realized_pnl,
+                    fees: pos.fees,
+                    net_pnl: pos.net_pnl(),
+                    volume: pos.maker_volume + pos.taker_volume,
+                })
+            })
+            .collect();
+        views.sort_by(|a, b| (&a.tag, &a.symbol).cmp(&(&b.tag, &b.symbol)));
+        views
     }
 
     pub fn order_views(&self) -> Vec<super::snapshot::OrderView> {
@@ -66,116 +437,383 @@ impl OmsEngine {
         self.trading_state = trading_state;
     }
 
+    /// Whether an order of `qty` on `side` only shrinks |net_qty| on `symbol`
+    pub fn reduces(&self, symbol: &str, side: Side, qty: Decimal) -> bool {
+        let net = self.position(symbol).map_or(dec!(0), |p| p.net_qty);
+        net != dec!(0) && side.sign() != net.signum() && qty <= net.abs()
+    }
+
+    /// Overwrites the position with venue truth, keeping realized pnl
+    pub fn sync_position(&mut self, symbol: &str, net_qty: Decimal, avg_price: Decimal) {
+        let cost_basis = self.cost_basis;
+        self.positions
+            .entry(symbol.to_string())
+            .or_insert_with(|| Position::with_cost_basis(cost_basis))
+            .sync(net_qty, avg_price);
+
+        self.core_mut(symbol).set_filled_position(Quantity(net_qty));
+    }
+
+    /// Books a signed fill that belongs to no order, e.g. one we missed on
+    /// the WS and only learned about from the venue position
+    pub fn on_adjustment_fill(&mut self, symbol: &str, qty: Decimal, price: Decimal) {
+        let cost_basis = self.cost_basis;
+        self.positions
+            .entry(symbol.to_string())
+            .or_insert_with(|| Position::with_cost_basis(cost_basis))
+            .apply_fill(qty, price);
+
+        self.core_mut(symbol).on_fill(Quantity(qty));
+    }
+
+    pub fn orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.values()
+    }
+
     /* ---------- Order lifecycle ---------- */
 
-    pub fn create_order(&mut self, side: Side, qty: Decimal, price: Decimal) -> OrderId {
-        let order = Order::new(side, qty, price);
+    pub fn create_order(&mut self, symbol: &str, side: Side, qty: Decimal, price: Decimal) -> OrderId {
+        self.insert_order(Order::new(symbol, side, qty, price))
+    }
+
+    pub fn insert_order(&mut self, order: Order) -> OrderId {
+        // make sure the symbol shows up in delta/snapshots before any ack
+        self.core_mut(&order.symbol);
+
         let id = order.id;
         self.orders.insert(id, order);
         id
     }
 
-    pub fn on_order_accepted(&mut self, id: OrderId) {
-        let order = self.orders.get_mut(&id).expect("unknown order");
-        order.on_accepted();
+    pub fn order(&self, id: OrderId) -> Option<&Order> {
+        self.orders.get(&id)
+    }
+
+    fn order_mut(&mut self, id: OrderId) -> Result<&mut Order, OrderError> {
+        self.orders.get_mut(&id).ok_or(OrderError::UnknownOrder(id))
+    }
+
+    pub fn on_order_sent(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_sent()?;
         self.recompute_open_exposure();
+        Ok(())
     }
 
-    pub fn on_order_rejected(&mut self, id: OrderId) {
-        let order = self.orders.get_mut(&id).expect("unknown order");
-        order.on_rejected();
+    pub fn on_status_unknown(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_status_unknown();
         self.recompute_open_exposure();
+        Ok(())
     }
 
-    pub fn on_fill(&mut self, id: OrderId, fill_qty: Decimal, price: Decimal) {
-        let order = self.orders.get_mut(&id).expect("unknown order");
-        order.on_fill(fill_qty);
+    pub fn on_order_accepted(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_accepted();
+        self.recompute_open_exposure();
+        Ok(())
+    }
+
+    pub fn on_order_rejected(&mut self, id: OrderId, reason: Option<RejectReason>) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_rejected(reason)?;
+        self.recompute_open_exposure();
+        Ok(())
+    }
+
+    /// Applies a fill. Fills are exchange truth, so the position is booked
+    /// even if the order's own bookkeeping disagrees (e.g. an overfill);
+    /// the disagreement is still returned as an error. A trade id already
+    /// booked is refused without touching anything.
+    pub fn on_fill(&mut self, fill: &Fill) -> Result<(), OrderError> {
+        let order = self.order_mut(fill.order_id)?;
+        if fill.qty <= dec!(0) {
+            return Err(OrderError::NonPositiveFill { order_id: fill.order_id, qty: fill.qty });
+        }
+        if !order.record_trade(&fill.trade_id) {
+            return Err(OrderError::DuplicateFill {
+                order_id: fill.order_id,
+                trade_id: fill.trade_id.clone(),
+            });
+        }
+        let transition = order.on_fill(fill.qty);
 
         // truth update
-        let signed = fill_qty * order.side.sign();
+        let signed = fill.qty * order.side.sign();
+        let symbol = order.symbol.clone();
 
-        // update position economics
-        self.position.apply_fill(signed, price);
+        // update position economics, fees and volume
+        let tag = order.tag.clone();
+        let side = order.side;
+        let cost_basis = self.cost_basis;
+        self.positions
+            .entry(symbol.clone())
+            .or_insert_with(|| Position::with_cost_basis(cost_basis))
+            .on_fill(signed, fill);
+
+        match tag {
+            // the owner keeps all of it
+            Some(tag) => self.tag_position_mut(&tag, &symbol).on_fill(signed, fill),
+
+            // worked for the netted target: split among strategies that
+            // wanted this direction; anything past their wants stays unowned
+            None => {
+                let wants: Vec<(String, Decimal)> = self
+                    .strategy_wants(&symbol)
+                    .into_iter()
+                    .filter(|(_, w)| w.signum() == side.sign())
+                    .map(|(s, w)| (s, w.abs()))
+                    .collect();
+                for (strategy, share) in pro_rata(&wants, fill.qty) {
+                    let part = Fill {
+                        qty: share,
+                        fee: fill.fee * share / fill.qty,
+                        ..fill.clone()
+                    };
+                    self.tag_position_mut(&strategy, &symbol).on_fill(share * side.sign(), &part);
+                }
+            }
+        }
 
         // update reconciliation truth
-        self.core.on_fill(Quantity(signed));
+        self.core_mut(&symbol).on_fill(Quantity(signed));
 
         self.recompute_open_exposure();
+        transition
     }
 
-    pub fn request_cancel(&mut self, id: OrderId) {
-        let order = self.orders.get_mut(&id).expect("unknown order");
-        order.on_cancel_requested();
+    pub fn request_cancel(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_cancel_requested()?;
+        self.recompute_open_exposure();
+        Ok(())
     }
 
-    pub fn on_cancel_confirmed(&mut self, id: OrderId) {
-        let order = self.orders.get_mut(&id).expect("unknown order");
-        order.on_cancel_confirmed();
+    pub fn on_cancel_rejected(&mut self, id: OrderId, reason: &CancelRejectReason) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_cancel_rejected(reason)?;
         self.recompute_open_exposure();
+        Ok(())
+    }
+
+    pub fn request_amend(&mut self, id: OrderId, price: Decimal, qty: Decimal) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_amend_requested(price, qty)?;
+        self.recompute_open_exposure();
+        Ok(())
+    }
+
+    pub fn on_amend_accepted(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_amend_accepted();
+        self.recompute_open_exposure();
+        Ok(())
+    }
+
+    pub fn on_amend_rejected(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_amend_rejected();
+        self.recompute_open_exposure();
+        Ok(())
+    }
+
+    pub fn on_cancel_confirmed(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_cancel_confirmed()?;
+        self.recompute_open_exposure();
+        Ok(())
+    }
+
+    pub fn on_order_expired(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.on_expired();
+        self.recompute_open_exposure();
+        Ok(())
     }
 
     /* ---------- Internal ---------- */
 
+    fn core_mut(&mut self, symbol: &str) -> &mut OmsCore {
+        self.cores
+            .entry(symbol.to_string())
+            .or_insert_with(OmsCore::new)
+    }
+
     fn recompute_open_exposure(&mut self) {
-        self.core.clear_open_orders();
+        for core in self.cores.values_mut() {
+            core.clear_open_orders();
+        }
 
         for order in self.orders.values() {
             let qty = order.remaining_signed_qty();
             if qty != dec!(0) {
-                self.core.add_open_order(Quantity(qty));
+                self.cores
+                    .entry(order.symbol.clone())
+                    .or_insert_with(OmsCore::new)
+                    .add_open_order(Quantity(qty));
             }
         }
     }
 
-    pub fn open_order_ids(&self) -> Vec<OrderId> {
+    /// Live orders as (id, symbol), optionally restricted to one symbol.
+    /// Orders with an amend in flight are live and cancellable too.
+    pub fn open_orders(&self, symbol: Option<&str>) -> Vec<(OrderId, String)> {
+        self.open_orders_tagged(symbol, None)
+    }
+
+    /// `open_orders`, further restricted to one tag when given
+    pub fn open_orders_tagged(&self, symbol: Option<&str>, tag: Option<&str>) -> Vec<(OrderId, String)> {
         self.orders
             .iter()
             .filter(|(_, o)| matches!(
                     o.state,
                     super::order::OrderState::Open { .. }
                     | super::order::OrderState::PartiallyFilled { .. }
+                    | super::order::OrderState::ReplacePending { .. }
+                    | super::order::OrderState::PendingAck
+                    | super::order::OrderState::Unknown
             ))
-            .map(|(id, _)| *id)
+            .filter(|(_, o)| symbol.is_none_or(|s| o.symbol == s))
+            .filter(|(_, o)| tag.is_none_or(|t| o.tag.as_deref() == Some(t)))
+            .map(|(id, o)| (*id, o.symbol.clone()))
+            .collect()
+    }
+
+    /// Whether an order on `symbol` has a cancel or amend the venue has not
+    /// answered yet
+    pub fn has_change_in_flight(&self, symbol: &str) -> bool {
+        self.orders.values().any(|o| {
+            o.symbol == symbol
+                && matches!(
+                    o.state,
+                    super::order::OrderState::CancelPending { .. } | super::order::OrderState::ReplacePending { .. }
+                )
+        })
+    }
+
+    /// Orders sent to the venue that have not been acked yet, as (id, symbol)
+    pub fn unacked_orders(&self) -> Vec<(OrderId, String)> {
+        self.orders
+            .iter()
+            .filter(|(_, o)| o.is_unacked())
+            .map(|(id, o)| (*id, o.symbol.clone()))
             .collect()
     }
 }
 
+/// Splits `qty` across `wants` in proportion, never giving anyone more
+/// than they want. The last share takes the rounding remainder.
+fn pro_rata(wants: &[(String, Decimal)], qty: Decimal) -> Vec<(String, Decimal)> {
+    let total: Decimal = wants.iter().map(|(_, w)| *w).sum();
+    if total <= qty {
+        return wants.to_vec();
+    }
+
+    let mut left = qty;
+    let mut shares = Vec::with_capacity(wants.len());
+    for (i, (strategy, want)) in wants.iter().enumerate() {
+        let share = if i + 1 == wants.len() { left } else { qty * want / total };
+        left -= share;
+        shares.push((strategy.clone(), share));
+    }
+    shares
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::oms::order::OrderState;
     use rust_decimal_macros::dec;
 
     #[test]
     fn end_to_end_reconciliation() {
         let mut oms = OmsEngine::new();
 
-        oms.set_target_position(dec!(1.0));
-        assert_eq!(oms.delta(), dec!(1.0));
+        oms.set_target_position("TST", dec!(1.0));
+        assert_eq!(oms.delta("TST"), dec!(1.0));
 
-        let oid = oms.create_order(Side::Buy, dec!(1.0));
-        oms.on_order_accepted(oid);
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
 
-        assert_eq!(oms.delta(), dec!(0.0));
+        assert_eq!(oms.delta("TST"), dec!(0.0));
 
-        oms.on_fill(oid, dec!(0.4), dec!(100));
-        assert_eq!(oms.delta(), dec!(0.0));
+        oms.on_fill(&Fill::new(oid, dec!(0.4), dec!(100))).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
 
-        oms.on_fill(oid, dec!(0.6), dec!(100));
-        assert_eq!(oms.delta(), dec!(0.0));
+        oms.on_fill(&Fill::new(oid, dec!(0.6), dec!(100))).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
     }
 
     #[test]
     fn cancel_releases_exposure() {
         let mut oms = OmsEngine::new();
 
-        oms.set_target_position(dec!(1.0));
-        let oid = oms.create_order(Side::Buy, dec!(1.0));
-        oms.on_order_accepted(oid);
+        oms.set_target_position("TST", dec!(1.0));
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+
+        // still live until the venue confirms
+        oms.request_cancel(oid).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
+
+        oms.on_cancel_confirmed(oid).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(1.0));
+    }
+
+    #[test]
+    fn cancel_reject_keeps_or_resolves_exposure() {
+        let mut oms = OmsEngine::new();
+
+        oms.set_target_position("TST", dec!(1.0));
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+
+        oms.request_cancel(oid).unwrap();
+        oms.on_cancel_rejected(oid, &CancelRejectReason::Transport("timeout".into())).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
+        assert_eq!(oms.open_orders(None), vec![(oid, "TST".to_string())]);
+
+        oms.request_cancel(oid).unwrap();
+        oms.on_cancel_rejected(oid, &CancelRejectReason::UnknownOrder).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(1.0));
+        assert!(oms.open_orders(None).is_empty());
+
+        // traded out: exposure holds until the fill moves the position
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+        oms.request_cancel(oid).unwrap();
+        oms.on_cancel_rejected(oid, &CancelRejectReason::AlreadyFilled).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
+
+        oms.on_fill(&Fill::new(oid, dec!(1.0), dec!(100))).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
+        assert_eq!(oms.order(oid).unwrap().state, OrderState::Filled);
+    }
+
+    #[test]
+    fn amend_keeps_exposure_until_confirmed() {
+        let mut oms = OmsEngine::new();
+
+        oms.set_target_position("TST", dec!(2.0));
+        let oid = oms.create_order("TST", Side::Buy, dec!(2.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
 
-        oms.request_cancel(oid);
-        oms.on_cancel_confirmed(oid);
+        // shrinking: old size still counts until the venue confirms
+        oms.request_amend(oid, dec!(101), dec!(1.0)).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
 
-        assert_eq!(oms.delta(), dec!(1.0));
+        oms.on_amend_accepted(oid).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(1.0));
+        assert_eq!(oms.order(oid).unwrap().limit_price, dec!(101));
+    }
+
+    #[test]
+    fn unacked_order_is_exposure() {
+        let mut oms = OmsEngine::new();
+
+        oms.set_target_position("TST", dec!(1.0));
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_sent(oid).unwrap();
+        assert_eq!(oms.delta("TST"), dec!(0.0));
+
+        oms.on_status_unknown(oid).unwrap();
+        assert_eq!(oms.unacked_orders(), vec![(oid, "TST".to_string())]);
+        assert_eq!(oms.delta("TST"), dec!(0.0));
+
+        // venue never had it
+        oms.on_order_rejected(oid, None).unwrap();
+        assert!(oms.unacked_orders().is_empty());
+        assert_eq!(oms.delta("TST"), dec!(1.0));
     }
 
     #[test]
@@ -184,15 +822,223 @@ mod tests {
 
         let mut oms = OmsEngine::new();
 
-        oms.set_target_position(dec!(1.0));
-        let oid = oms.create_order(Side::Buy, dec!(1.0));
-        oms.on_order_accepted(oid);
+        oms.set_target_position("TST", dec!(1.0));
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
 
-        oms.on_fill(oid, dec!(0.4), dec!(100.0));
-        oms.on_fill(oid, dec!(0.6), dec!(101.0));
+        oms.on_fill(&Fill::new(oid, dec!(0.4), dec!(100.0))).unwrap();
+        oms.on_fill(&Fill::new(oid, dec!(0.6), dec!(101.0))).unwrap();
 
-        let pos = oms.position();
+        let pos = oms.position("TST").unwrap();
         assert_eq!(pos.net_qty, dec!(1.0));
         assert_eq!(pos.avg_price, dec!(100.6));
     }
+
+    #[test]
+    fn tagged_fills_are_attributed_and_cancels_scoped() {
+        let mut oms = OmsEngine::new();
+
+        let mm = oms.insert_order(Order::new("TST", Side::Buy, dec!(2), dec!(100)).with_tag(Some("mm".into())));
+        let manual = oms.insert_order(Order::new("TST", Side::Sell, dec!(1), dec!(105)).with_tag(Some("manual".into())));
+        let untagged = oms.create_order("TST", Side::Buy, dec!(1), dec!(99));
+        for id in [mm, manual, untagged] {
+            oms.on_order_accepted(id).unwrap();
+        }
+
+        oms.on_fill(&Fill::new(mm, dec!(1), dec!(100)).with_fee(dec!(0.01), "USDC")).unwrap();
+        oms.on_fill(&Fill::new(manual, dec!(1), dec!(105))).unwrap();
+
+        assert_eq!(oms.position("TST").unwrap().net_qty, dec!(0));
+        assert_eq!(oms.tag_position("mm", "TST").unwrap().net_qty, dec!(1));
+        assert_eq!(oms.tag_position("manual", "TST").unwrap().net_qty, dec!(-1));
+
+        let views = oms.tag_views();
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[1].tag, "mm");
+        assert_eq!(views[1].net_pnl, dec!(-0.01));
+        assert_eq!(views[1].volume, dec!(100));
+
+        assert_eq!(oms.open_orders_tagged(Some("TST"), Some("mm")), vec![(mm, "TST".to_string())]);
+        assert_eq!(oms.open_orders_tagged(Some("TST"), None).len(), 2);
+    }
+
+    #[test]
+    fn strategy_targets_net_cross_and_allocate() {
+        let mut oms = OmsEngine::new();
+        oms.set_strategy_target("TST", Some("a"), dec!(3));
+        oms.set_strategy_target("TST", Some("b"), dec!(-1));
+        oms.set_strategy_target("TST", Some("c"), dec!(1));
+
+        // venue only sees the net
+        assert_eq!(oms.delta("TST"), dec!(3));
+        assert_eq!(oms.crossable("TST"), dec!(1));
+
+        // b's sell meets the buyers inside, pro rata 3:1
+        assert_eq!(oms.cross_internal("TST", dec!(100)), dec!(1));
+        assert_eq!(oms.tag_position("b", "TST").unwrap().net_qty, dec!(-1));
+        assert_eq!(oms.tag_position("a", "TST").unwrap().net_qty, dec!(0.75));
+        assert_eq!(oms.tag_position("c", "TST").unwrap().net_qty, dec!(0.25));
+        assert!(oms.position("TST").is_none());
+        assert_eq!(oms.crossable("TST"), dec!(0));
+
+        // a venue fill for the net goes to the buyers by what they still want
+        let oid = oms.create_order("TST", Side::Buy, dec!(3), dec!(101));
+        oms.on_order_accepted(oid).unwrap();
+        oms.on_fill(&Fill::new(oid, dec!(3), dec!(101)).with_fee(dec!(0.3), "USDC")).unwrap();
+
+        let a = oms.tag_position("a", "TST").unwrap();
+        assert_eq!(a.net_qty, dec!(3));
+        assert_eq!(a.fees, dec!(0.225));
+        assert_eq!(oms.tag_position("c", "TST").unwrap().net_qty, dec!(1));
+        assert_eq!(oms.position("TST").unwrap().net_qty, dec!(3));
+        assert_eq!(oms.delta("TST"), dec!(0));
+        assert!(oms.strategy_wants("TST").is_empty());
+    }
+
+    #[test]
+    fn positions_are_marked_to_market() {
+        let mut oms = OmsEngine::new();
+        let oid = oms.create_order("TST", Side::Sell, dec!(2), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+        oms.on_fill(&Fill::new(oid, dec!(2), dec!(100)).with_fee(dec!(0.1), "USDC")).unwrap();
+
+        let view = &oms.position_views()[0];
+        assert_eq!(view.mark, None);
+        assert_eq!(view.unrealized_pnl, dec!(0));
+
+        oms.set_mark("TST", dec!(95));
+        let view = &oms.position_views()[0];
+        assert_eq!(view.unrealized_pnl, dec!(10));
+        assert_eq!(view.total_pnl, dec!(9.9));
+        assert_eq!(view.notional, dec!(-190));
+    }
+
+    #[test]
+    fn fees_and_liquidity_are_tracked_apart_from_gross_pnl() {
+        use crate::oms::fill::Liquidity;
+
+        let mut oms = OmsEngine::new();
+
+        let buy = oms.create_order("TST", Side::Buy, dec!(1), dec!(100));
+        oms.on_order_accepted(buy).unwrap();
+        oms.on_fill(&Fill::new(buy, dec!(1), dec!(100)).with_fee(dec!(-0.02), "USDC").with_liquidity(Liquidity::Maker))
+            .unwrap();
+
+        let sell = oms.create_order("TST", Side::Sell, dec!(1), dec!(110));
+        oms.on_order_accepted(sell).unwrap();
+        oms.on_fill(&Fill::new(sell, dec!(1), dec!(110)).with_fee(dec!(0.05), "USDC")).unwrap();
+
+        let pos = oms.position("TST").unwrap();
+        assert_eq!(pos.realized_pnl, dec!(10));
+        assert_eq!(pos.fees, dec!(0.03));
+        assert_eq!(pos.net_pnl(), dec!(9.97));
+        assert_eq!(pos.maker_volume, dec!(100));
+        assert_eq!(pos.taker_volume, dec!(110));
+    }
+
+    #[test]
+    fn symbols_are_tracked_independently() {
+        let mut oms = OmsEngine::new();
+
+        oms.set_target_position("BTC", dec!(1.0));
+        oms.set_target_position("ETH", dec!(-2.0));
+
+        let btc = oms.create_order("BTC", Side::Buy, dec!(1.0), dec!(50000));
+        let eth = oms.create_order("ETH", Side::Sell, dec!(0.5), dec!(3000));
+        oms.on_order_accepted(btc).unwrap();
+        oms.on_order_accepted(eth).unwrap();
+
+        assert_eq!(oms.delta("BTC"), dec!(0.0));
+        assert_eq!(oms.delta("ETH"), dec!(-1.5));
+
+        oms.on_fill(&Fill::new(eth, dec!(0.5), dec!(3000))).unwrap();
+        assert_eq!(oms.position("ETH").unwrap().net_qty, dec!(-0.5));
+        assert!(oms.position("BTC").is_none());
+
+        assert_eq!(oms.open_orders(Some("BTC")), vec![(btc, "BTC".to_string())]);
+        assert!(oms.open_orders(Some("ETH")).is_empty());
+        assert_eq!(oms.symbols(), vec!["BTC".to_string(), "ETH".to_string()]);
+    }
+
+    #[test]
+    fn unknown_and_late_events_do_not_panic() {
+        let mut oms = OmsEngine::new();
+        let ghost = OrderId(uuid::Uuid::new_v4());
+
+        assert_eq!(oms.on_fill(&Fill::new(ghost, dec!(1), dec!(100))), Err(OrderError::UnknownOrder(ghost)));
+        assert_eq!(oms.on_cancel_confirmed(ghost), Err(OrderError::UnknownOrder(ghost)));
+
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+        oms.on_fill(&Fill::new(oid, dec!(1.0), dec!(100))).unwrap();
+
+        assert!(oms.request_cancel(oid).is_err());
+        assert!(oms.on_order_rejected(oid, None).is_err());
+
+        // an overfill is reported but the position still follows the venue
+        let oid = oms.create_order("TST", Side::Buy, dec!(1.0), dec!(100));
+        assert!(matches!(oms.on_fill(&Fill::new(oid, dec!(2.0), dec!(100))), Err(OrderError::Overfill { .. })));
+        assert_eq!(oms.position("TST").unwrap().net_qty, dec!(3.0));
+    }
+
+    #[test]
+    fn redelivered_fill_is_booked_once() {
+        let mut oms = OmsEngine::new();
+        let oid = oms.create_order("TST", Side::Buy, dec!(2.0), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+
+        let fill = Fill::new(oid, dec!(2.0), dec!(100));
+        oms.on_fill(&fill).unwrap();
+        assert!(matches!(oms.on_fill(&fill), Err(OrderError::DuplicateFill { .. })));
+
+        // also once the order is done, where nothing else would catch it
+        let mut part = Fill::new(oid, dec!(1.0), dec!(100));
+        part.trade_id = fill.trade_id.clone();
+        assert!(matches!(oms.on_fill(&part), Err(OrderError::DuplicateFill { .. })));
+
+        assert_eq!(oms.position("TST").unwrap().net_qty, dec!(2.0));
+        assert_eq!(oms.order(oid).unwrap().state, OrderState::Filled);
+    }
+
+    #[test]
+    fn reduces_only_shrinks_the_position() {
+        let mut oms = OmsEngine::new();
+        assert!(!oms.reduces("TST", Side::Sell, dec!(1)));
+
+        let oid = oms.create_order("TST", Side::Buy, dec!(2), dec!(100));
+        oms.on_order_accepted(oid).unwrap();
+        oms.on_fill(&Fill::new(oid, dec!(2), dec!(100))).unwrap();
+
+        assert!(oms.reduces("TST", Side::Sell, dec!(2)));
+        assert!(!oms.reduces("TST", Side::Sell, dec!(3)));
+        assert!(!oms.reduces("TST", Side::Buy, dec!(1)));
+    }
+
+    #[test]
+    fn validate_snaps_to_grid_or_names_the_broken_rule() {
+        let mut oms = OmsEngine::new();
+        oms.set_instruments(Arc::new([Instrument::perp("TST", 2)].into_iter().collect()));
+
+        let mut bid = Order::new("TST", Side::Buy, dec!(1.239), dec!(12.34567));
+        assert_eq!(oms.validate(&mut bid), Ok(()));
+        assert_eq!((bid.original_qty, bid.limit_price), (dec!(1.23), dec!(12.345)));
+
+        let mut dust = Order::new("TST", Side::Sell, dec!(0.004), dec!(100));
+        assert_eq!(oms.validate(&mut dust), Err(RejectReason::ZeroQty { requested: dec!(0.004) }));
+
+        let mut small = Order::new("TST", Side::Sell, dec!(0.05), dec!(100));
+        assert_eq!(
+            oms.validate(&mut small),
+            Err(RejectReason::BelowMinNotional { notional: dec!(5), min: dec!(10) })
+        );
+
+        let mut free = Order::new("TST", Side::Buy, dec!(1), dec!(0));
+        assert_eq!(oms.validate(&mut free), Err(RejectReason::NonPositivePrice));
+
+        let mut other = Order::new("NOPE", Side::Buy, dec!(1), dec!(100));
+        assert_eq!(oms.validate(&mut other), Err(RejectReason::UnknownSymbol));
+
+        assert_eq!(oms.tradable_qty("TST", dec!(1.239), dec!(100)), dec!(1.23));
+        assert_eq!(oms.tradable_qty("TST", dec!(0.099), dec!(100)), dec!(0));
+    }
 }
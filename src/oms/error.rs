@@ -0,0 +1,150 @@
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+
+use super::order::{OrderId, OrderState, RejectReason};
+
+/// Why an event could not be applied to an order.
+///
+/// Exchange messages arrive late, twice or out of order; none of these
+/// should take the OMS down, so transitions report them instead of panicking.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderError {
+    /// No order with this id exists in the OMS
+    UnknownOrder(OrderId),
+
+    /// The event makes no sense in the order's current state
+    InvalidTransition {
+        order_id: OrderId,
+        from: OrderState,
+        event: &'static str,
+    },
+
+    /// Fill quantity was zero or negative
+    NonPositiveFill { order_id: OrderId, qty: Decimal },
+
+    /// Fill larger than what was left on the order. The fill is booked
+    /// anyway: the venue says it traded.
+    Overfill {
+        order_id: OrderId,
+        remaining: Decimal,
+        fill_qty: Decimal,
+    },
+
+    /// A fill with this venue trade id is already booked on the order
+    DuplicateFill { order_id: OrderId, trade_id: String },
+}
+
+impl OrderError {
+    pub fn order_id(&self) -> OrderId {
+        match self {
+            OrderError::UnknownOrder(id) => *id,
+            OrderError::InvalidTransition { order_id, .. }
+            | OrderError::NonPositiveFill { order_id, .. }
+            | OrderError::Overfill { order_id, .. }
+            | OrderError::DuplicateFill { order_id, .. } => *order_id,
+        }
+    }
+
+    /// Whether the event took effect despite the error
+    pub fn was_applied(&self) -> bool {
+        matches!(self, OrderError::Overfill { .. })
+    }
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::UnknownOrder(id) => write!(f, "unknown order {}", id),
+            OrderError::InvalidTransition { order_id, from, event } => {
+                write!(f, "{} on order {} in state {:?}", event, order_id, from)
+            }
+            OrderError::NonPositiveFill { order_id, qty } => {
+                write!(f, "non-positive fill {} on order {}", qty, order_id)
+            }
+            OrderError::Overfill { order_id, remaining, fill_qty } => write!(
+                f,
+                "fill {} exceeds remaining {} on order {}",
+                fill_qty, remaining, order_id
+            ),
+            OrderError::DuplicateFill { order_id, trade_id } => {
+                write!(f, "trade {} already booked on order {}", trade_id, order_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Why a journal entry did not take effect
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitError {
+    /// Could not be written to the journal, so it was not applied either
+    Journal(io::ErrorKind),
+
+    /// Journaled, but the engine refused it
+    Refused(OrderError),
+}
+
+impl fmt::Display for CommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitError::Journal(kind) => write!(f, "journal write failed: {}", kind),
+            CommitError::Refused(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CommitError {}
+
+/// An event the OMS could not apply cleanly, pushed to RMS so repeated
+/// anomalies can stop trading. Refused ones are also kept in quarantine.
+#[derive(Debug, Clone)]
+pub struct OmsAnomaly {
+    pub error: OrderError,
+    pub event: String,
+    /// The event still took effect; see `OrderError::was_applied`
+    pub applied: bool,
+}
+
+/// Why an `OmsHandle` call got no useful answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum OmsError {
+    /// The OMS task is gone
+    Closed,
+
+    /// No answer within the handle's timeout. The request may already be
+    /// queued: for calls that change something the outcome is unknown, and
+    /// the OMS can still apply it after the caller gave up.
+    Timeout(Duration),
+
+    /// `place` timed out after handing the order over; it may or may not
+    /// exist. Look it up with `OmsHandle::order` before placing it again.
+    Unconfirmed(OrderId),
+
+    /// The OMS is up but has nothing to answer with yet
+    NotReady(&'static str),
+
+    /// The OMS refused the request
+    Rejected(String),
+
+    /// The order broke an instrument rule and was never sent
+    Invalid(RejectReason),
+}
+
+impl fmt::Display for OmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OmsError::Closed => write!(f, "OMS is not running"),
+            OmsError::Timeout(after) => write!(f, "OMS did not answer within {:?}", after),
+            OmsError::Unconfirmed(id) => write!(f, "OMS did not confirm order {} in time", id),
+            OmsError::NotReady(what) => write!(f, "OMS has no {} yet", what),
+            OmsError::Rejected(reason) => write!(f, "OMS rejected the request: {}", reason),
+            OmsError::Invalid(reason) => write!(f, "order rejected locally: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for OmsError {}
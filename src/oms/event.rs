@@ -1,21 +1,90 @@
 use rust_decimal::Decimal;
 use tokio::sync::oneshot;
 
-use super::order::{OrderId, Side};
-use crate::oms::snapshot::OmsSnapshot;
+use super::order::{CancelRejectReason, OrderId, RejectReason, Side, TimeInForce, OrderFlags};
+use super::algo::{AlgoId, AlgoKind, AlgoView};
+use super::executor::ExecParams;
+use super::error::{OmsAnomaly, OmsError};
+use super::fill::Fill;
+use super::ledger::LotRecord;
+use crate::market::types::{MarkPrice, MarketSnapshot, Trade};
+use crate::oms::snapshot::{OmsSnapshot, OrderView};
 use crate::oms::account::AccountSnapshot;
 
+/// One symbol's worth of a flatten: the signed position to close and the
+/// worst price we are willing to cross at.
+#[derive(Debug, Clone)]
+pub struct FlattenLeg {
+    pub symbol: String,
+    pub qty: Decimal,
+    pub limit_px: Decimal,
+}
+
 #[derive(Debug)]
 pub enum OmsEvent {
     // strategy → OMS
+    /// With `exec` the OMS works the delta itself; without, this strategy
+    /// withdraws its exec params and places its own orders. The symbol's
+    /// netted delta is worked while any strategy still has params on it.
     SetTarget {
+        symbol: String,
         qty: Decimal,
+        exec: Option<ExecParams>,
+        /// Whose target this is. Targets are netted per symbol and fills
+        /// are allocated back; `None` is the shared, unnamed target.
+        strategy: Option<String>,
     },
 
     CreateOrder {
+        /// Picked by the sender, so it can look the order up even if the
+        /// reply never reaches it
+        order_id: OrderId,
+        symbol: String,
         side: Side,
         qty: Decimal,
         price: Decimal,
+        tif: TimeInForce,
+        flags: OrderFlags,
+        /// Owning strategy; see `Order::tag`
+        tag: Option<String>,
+        /// Told the order id, or why the OMS would not take it
+        reply: Option<oneshot::Sender<Result<OrderId, OmsError>>>,
+    },
+
+    /// Move a live order in place instead of cancel + create.
+    /// `qty` is the size the order should rest with afterwards.
+    AmendOrder {
+        order_id: OrderId,
+        price: Decimal,
+        qty: Decimal,
+    },
+
+    /// Parent order the OMS slices into children. `limit_price` is the
+    /// worst price any child may trade at. Refused unless trading is
+    /// `Running`.
+    StartAlgo {
+        symbol: String,
+        side: Side,
+        qty: Decimal,
+        limit_price: Decimal,
+        kind: AlgoKind,
+        reply: oneshot::Sender<Result<AlgoId, OmsError>>,
+    },
+
+    PauseAlgo {
+        id: AlgoId,
+    },
+
+    ResumeAlgo {
+        id: AlgoId,
+    },
+
+    CancelAlgo {
+        id: AlgoId,
+    },
+
+    GetAlgos {
+        reply: oneshot::Sender<Vec<AlgoView>>,
     },
 
     // exchange → OMS (later broker)
@@ -25,30 +94,83 @@ pub enum OmsEvent {
 
     OrderRejected {
         order_id: OrderId,
+        reason: RejectReason,
     },
 
-    Fill {
+    /// Send failed in transit; the order may or may not be live
+    OrderStatusUnknown {
         order_id: OrderId,
-        qty: Decimal,
-        price: Decimal,
+    },
+
+    /// Status query found no such order. It may still be on its way, so
+    /// the order stays unacked and is asked about again.
+    OrderNotFound {
+        order_id: OrderId,
+    },
+
+    Fill {
+        fill: Fill,
     },
 
     CancelConfirmed {
         order_id: OrderId,
     },
 
+    /// Venue did not cancel; `reason` says what became of the order
+    CancelRejected {
+        order_id: OrderId,
+        reason: CancelRejectReason,
+    },
+
+    /// Venue closed the order on its own: IOC remainder, margin or
+    /// self-trade cancel, dead-man fire
+    OrderExpired {
+        order_id: OrderId,
+    },
+
+    AmendAccepted {
+        order_id: OrderId,
+    },
+
+    AmendRejected {
+        order_id: OrderId,
+    },
+
     GetDelta {
+        symbol: String,
         reply: oneshot::Sender<rust_decimal::Decimal>,
     },
 
-    CancelAll,
+    /// Cancel every live order, or only those on `symbol` and/or with `tag`
+    CancelAll {
+        symbol: Option<String>,
+        tag: Option<String>,
+    },
 
     GetSnapshot {
         reply: oneshot::Sender<OmsSnapshot>,
     },
 
+    /// One order as the OMS sees it; `None` if it never took it
+    GetOrder {
+        order_id: OrderId,
+        reply: oneshot::Sender<Option<OrderView>>,
+    },
+
+    /// Every closed lot in the ledger, oldest first, optionally for one symbol
+    GetClosedLots {
+        symbol: Option<String>,
+        reply: oneshot::Sender<Result<Vec<LotRecord>, OmsError>>,
+    },
+
+    /// Most recent refused events, oldest first
+    GetQuarantine {
+        reply: oneshot::Sender<Vec<OmsAnomaly>>,
+    },
+
     GetAccountSnapshot {
-        reply: oneshot::Sender<AccountSnapshot>,
+        /// `None` until the first account poll has landed
+        reply: oneshot::Sender<Option<AccountSnapshot>>,
     },
 
     UpdateAccountSnapshot {
@@ -56,8 +178,35 @@ pub enum OmsEvent {
     },
 
     /// Emergency: flatten all exposure and stop
-    Flatten { qty : Decimal, limit_px : Decimal },
-    RiskKill { reason: String, qty : Decimal, limit_px : Decimal },
+    Flatten { legs: Vec<FlattenLeg> },
+    RiskKill { reason: String, legs: Vec<FlattenLeg> },
+
+    /// Stop quoting: live orders are cancelled, new orders and amends refused
+    PauseTrading { reason: String },
+
+    /// Back to `Running` from `Paused` or `ReduceOnly`; not from `Halted`
+    ResumeTrading { reason: String },
+
+    /// Accept only orders that shrink |net_qty|
+    ReduceOnly { reason: String },
+
+    /// Operator action that takes a `Halted` OMS back to `Running`
+    Rearm { operator: String, reason: String },
+
+    // market → OMS, for the executor
+    MarketData {
+        snapshot: MarketSnapshot,
+    },
+
+    // market → OMS, for VWAP participation
+    MarketTrade {
+        trade: Trade,
+    },
+
+    // market → OMS, venue mark for position marking
+    MarketMark {
+        mark: MarkPrice,
+    },
 
     // internal
     Tick,
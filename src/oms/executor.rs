@@ -0,0 +1,435 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+use super::engine::OmsEngine;
+use super::order::{OrderId, OrderState, Side, TimeInForce};
+use crate::market::types::MarketSnapshot;
+
+/// How the OMS should work a symbol's delta into the market
+#[derive(Debug, Clone)]
+pub struct ExecParams {
+    /// Largest single child order
+    pub max_child_qty: Decimal,
+
+    /// A resting child is moved to the current price at most this often
+    pub reprice_after: Duration,
+
+    /// Where in the spread to price: 0 joins our own touch (post-only),
+    /// 1 takes the far touch, anything between sits inside the spread
+    pub aggressiveness: Decimal,
+}
+
+impl Default for ExecParams {
+    fn default() -> Self {
+        Self {
+            max_child_qty: dec!(1),
+            reprice_after: Duration::from_secs(5),
+            aggressiveness: dec!(0),
+        }
+    }
+}
+
+/// What the executor wants the OMS to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecAction {
+    Place {
+        /// Picked by the executor, so it knows its own children
+        order_id: OrderId,
+        symbol: String,
+        side: Side,
+        qty: Decimal,
+        price: Decimal,
+        tif: TimeInForce,
+    },
+    Amend {
+        order_id: OrderId,
+        price: Decimal,
+        qty: Decimal,
+    },
+    Cancel {
+        order_id: OrderId,
+    },
+}
+
+/// Works `OmsCore::delta` into child orders for symbols that asked for it.
+///
+/// One child per symbol at a time. Only orders the executor placed are its
+/// children; quotes, algo children and manual orders on the symbol are left
+/// alone, though they count in the delta it works. Params are kept per strategy; the symbol is worked while any strategy
+/// wants it, with the shared target's params first, then by strategy name.
+#[derive(Debug, Default)]
+pub struct Executor {
+    /// symbol → strategy (`None` for the shared target) → params
+    params: HashMap<String, BTreeMap<Option<String>, ExecParams>>,
+    /// (best bid, best ask)
+    touches: HashMap<String, (Decimal, Decimal)>,
+    /// Orders the executor placed and not yet seen finish. Not journaled:
+    /// after a restart, children of the last run are no longer worked.
+    children: HashSet<OrderId>,
+    /// When each child was last priced
+    priced_at: HashMap<OrderId, Instant>,
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `None` withdraws `strategy`'s params; once no strategy has any, the
+    /// symbol goes back to whoever sets its orders
+    pub fn set_params(&mut self, symbol: &str, strategy: Option<&str>, params: Option<ExecParams>) {
+        let strategy = strategy.map(str::to_string);
+        match params {
+            Some(p) => {
+                self.params.entry(symbol.to_string()).or_default().insert(strategy, p);
+            }
+            None => {
+                if let Some(by_strategy) = self.params.get_mut(symbol) {
+                    by_strategy.remove(&strategy);
+                    if by_strategy.is_empty() {
+                        self.params.remove(symbol);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Params the symbol is worked with, if anyone asked
+    fn params(&self, symbol: &str) -> Option<&ExecParams> {
+        self.params.get(symbol).and_then(|by_strategy| by_strategy.values().next())
+    }
+
+    pub fn is_executing(&self, symbol: &str) -> bool {
+        self.params(symbol).is_some()
+    }
+
+    pub fn symbols(&self) -> Vec<String> {
+        self.params.keys().cloned().collect()
+    }
+
+    pub fn on_book(&mut self, snapshot: &MarketSnapshot) {
+        if let (Some(bid), Some(ask)) = (snapshot.book.bids.first(), snapshot.book.asks.first()) {
+            self.touches.insert(snapshot.symbol.clone(), (bid.price, ask.price));
+        }
+    }
+
+    /// Price for a child on `side` given the current touch, on the
+    /// instrument's grid so it compares with what the order rests at
+    fn price(&self, engine: &OmsEngine, symbol: &str, params: &ExecParams, side: Side, bid: Decimal, ask: Decimal) -> Decimal {
+        let (near, far) = match side {
+            Side::Buy => (bid, ask),
+            Side::Sell => (ask, bid),
+        };
+        let price = near + params.aggressiveness * (far - near);
+        engine.instrument(symbol).map_or(price, |i| i.round_price(price, side))
+    }
+
+    /// Decides what to do for one symbol right now
+    pub fn plan(&mut self, engine: &OmsEngine, symbol: &str, now: Instant) -> Vec<ExecAction> {
+        let Some(params) = self.params(symbol).cloned() else {
+            return Vec::new();
+        };
+        let params = &params;
+        let Some(&(bid, ask)) = self.touches.get(symbol) else {
+            return Vec::new();
+        };
+
+        let delta = engine.delta(symbol);
+        self.children.retain(|id| engine.order(*id).is_some_and(|o| !o.is_terminal()));
+        let children: Vec<OrderId> = engine
+            .open_orders(Some(symbol))
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| self.children.contains(id))
+            .collect();
+
+        self.priced_at
+            .retain(|id, _| engine.order(*id).is_some_and(|o| o.remaining_signed_qty() != dec!(0)));
+
+        let mut actions = Vec::new();
+
+        if children.is_empty() {
+            if delta == dec!(0) {
+                return actions;
+            }
+            // a cancel still counts in delta until it resolves; placing
+            // against it would overshoot once it confirms
+            if engine.has_change_in_flight(symbol) {
+                return actions;
+            }
+
+            let side = if delta > dec!(0) { Side::Buy } else { Side::Sell };
+            let tif = if params.aggressiveness == dec!(0) {
+                TimeInForce::Alo
+            } else {
+                TimeInForce::Gtc
+            };
+
+            // a remainder under one lot or the minimum notional is as
+            // close to target as the venue allows
+            let price = self.price(engine, symbol, params, side, bid, ask);
+            let qty = engine.tradable_qty(symbol, delta.abs().min(params.max_child_qty), price);
+            if qty == dec!(0) {
+                return actions;
+            }
+
+            let order_id = OrderId(Uuid::new_v4());
+            self.children.insert(order_id);
+            actions.push(ExecAction::Place {
+                order_id,
+                symbol: symbol.to_string(),
+                side,
+                qty,
+                price,
+                tif,
+            });
+            return actions;
+        }
+
+        for id in children {
+            let Some(order) = engine.order(id) else { continue };
+
+            // target moved past us: this child now adds the wrong way
+            if delta * order.side.sign() < dec!(0) {
+                actions.push(ExecAction::Cancel { order_id: id });
+                continue;
+            }
+
+            let remaining = match order.state {
+                OrderState::Open { remaining } | OrderState::PartiallyFilled { remaining } => remaining,
+                // ack or amend in flight
+                _ => continue,
+            };
+
+            let priced_at = *self.priced_at.entry(id).or_insert(now);
+            let price = self.price(engine, symbol, params, order.side, bid, ask);
+
+            if price != order.limit_price && now.duration_since(priced_at) >= params.reprice_after {
+                self.priced_at.insert(id, now);
+                actions.push(ExecAction::Amend { order_id: id, price, qty: remaining });
+            }
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::instrument::Instrument;
+    use crate::market::types::{BookLevel, OrderBook};
+    use crate::oms::order::Order;
+
+    fn book(bid: Decimal, ask: Decimal) -> MarketSnapshot {
+        MarketSnapshot {
+            symbol: "TST".into(),
+            book: OrderBook {
+                bids: vec![BookLevel { price: bid, qty: dec!(10) }],
+                asks: vec![BookLevel { price: ask, qty: dec!(10) }],
+            },
+            timestamp_ms: 0,
+        }
+    }
+
+    /// The one Place in `actions`, with its id zeroed for comparing
+    fn placed(actions: Vec<ExecAction>) -> ExecAction {
+        match actions.as_slice() {
+            [ExecAction::Place { symbol, side, qty, price, tif, .. }] => ExecAction::Place {
+                order_id: OrderId(Uuid::nil()),
+                symbol: symbol.clone(),
+                side: *side,
+                qty: *qty,
+                price: *price,
+                tif: *tif,
+            },
+            other => panic!("expected one place, got {:?}", other),
+        }
+    }
+
+    fn place(side: Side, qty: Decimal, price: Decimal) -> ExecAction {
+        ExecAction::Place {
+            order_id: OrderId(Uuid::nil()),
+            symbol: "TST".into(),
+            side,
+            qty,
+            price,
+            tif: TimeInForce::Gtc,
+        }
+    }
+
+    /// Creates and acks the child the executor asked for, as the runtime would
+    fn start_child(engine: &mut OmsEngine, actions: Vec<ExecAction>) -> OrderId {
+        let [ExecAction::Place { order_id, symbol, side, qty, price, tif }] = actions.as_slice() else {
+            panic!("expected one place, got {:?}", actions);
+        };
+        let order = Order::new(symbol, *side, *qty, *price).with_id(*order_id).with_tif(*tif);
+        engine.insert_order(order);
+        engine.on_order_accepted(*order_id).unwrap();
+        *order_id
+    }
+
+    fn params() -> ExecParams {
+        ExecParams {
+            max_child_qty: dec!(2),
+            reprice_after: Duration::from_secs(5),
+            aggressiveness: dec!(0.5),
+        }
+    }
+
+    #[test]
+    fn places_capped_child_inside_spread() {
+        let mut engine = OmsEngine::new();
+        engine.set_target_position("TST", dec!(-5));
+
+        let mut exec = Executor::new();
+        exec.set_params("TST", None, Some(params()));
+
+        // no book yet → nothing
+        assert!(exec.plan(&engine, "TST", Instant::now()).is_empty());
+
+        exec.on_book(&book(dec!(100), dec!(102)));
+        assert_eq!(placed(exec.plan(&engine, "TST", Instant::now())), place(Side::Sell, dec!(2), dec!(101)));
+
+        // symbols without params are left alone
+        engine.set_target_position("OTHER", dec!(1));
+        assert!(exec.plan(&engine, "OTHER", Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn params_are_kept_per_strategy() {
+        let mut exec = Executor::new();
+        exec.set_params("TST", Some("a"), Some(params()));
+
+        // another strategy trading by hand does not switch off a's execution
+        exec.set_params("TST", Some("b"), None);
+        assert!(exec.is_executing("TST"));
+
+        exec.set_params("TST", Some("a"), None);
+        assert!(!exec.is_executing("TST"));
+        assert!(exec.symbols().is_empty());
+    }
+
+    #[test]
+    fn flip_waits_for_the_cancel_before_placing() {
+        let mut engine = OmsEngine::new();
+        let mut exec = Executor::new();
+        exec.set_params("TST", None, Some(params()));
+        exec.on_book(&book(dec!(100), dec!(102)));
+        let t0 = Instant::now();
+
+        engine.set_target_position("TST", dec!(1));
+        let actions = exec.plan(&engine, "TST", t0);
+        let child = start_child(&mut engine, actions);
+
+        engine.set_target_position("TST", dec!(-1));
+        assert_eq!(exec.plan(&engine, "TST", t0), vec![ExecAction::Cancel { order_id: child }]);
+        engine.request_cancel(child).unwrap();
+
+        // delta still carries the buy being cancelled: nothing new yet
+        assert_eq!(engine.delta("TST"), dec!(-2));
+        assert!(exec.plan(&engine, "TST", t0).is_empty());
+
+        // once it is gone, only the real delta goes out
+        engine.on_cancel_confirmed(child).unwrap();
+        assert_eq!(placed(exec.plan(&engine, "TST", t0)), place(Side::Sell, dec!(1), dec!(101)));
+    }
+
+    #[test]
+    fn untradable_remainder_leaves_executor_idle() {
+        let mut engine = OmsEngine::new();
+        engine.set_instruments(std::sync::Arc::new([Instrument::perp("TST", 2)].into_iter().collect()));
+        let mut exec = Executor::new();
+        exec.set_params("TST", None, Some(params()));
+        exec.on_book(&book(dec!(100), dec!(102)));
+
+        // 1.239 to go: one lot-rounded child
+        engine.set_target_position("TST", dec!(1.239));
+        assert!(matches!(
+            exec.plan(&engine, "TST", Instant::now()).as_slice(),
+            [ExecAction::Place { qty, .. }] if *qty == dec!(1.23)
+        ));
+
+        // under one lot, then under the minimum notional
+        engine.set_target_position("TST", dec!(0.009));
+        assert!(exec.plan(&engine, "TST", Instant::now()).is_empty());
+        engine.set_target_position("TST", dec!(0.05));
+        assert!(exec.plan(&engine, "TST", Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn reprices_after_interval_and_cancels_on_flip() {
+        let mut engine = OmsEngine::new();
+        engine.set_target_position("TST", dec!(1));
+
+        let mut exec = Executor::new();
+        exec.set_params("TST", None, Some(params()));
+        exec.on_book(&book(dec!(100), dec!(102)));
+
+        let t0 = Instant::now();
+        let actions = exec.plan(&engine, "TST", t0);
+        let child = start_child(&mut engine, actions);
+        assert!(exec.plan(&engine, "TST", t0).is_empty());
+
+        exec.on_book(&book(dec!(101), dec!(103)));
+
+        // book moved, but not for long enough
+        assert!(exec.plan(&engine, "TST", t0 + Duration::from_secs(1)).is_empty());
+        assert_eq!(
+            exec.plan(&engine, "TST", t0 + Duration::from_secs(6)),
+            vec![ExecAction::Amend { order_id: child, price: dec!(102), qty: dec!(1) }]
+        );
+
+        engine.set_target_position("TST", dec!(-1));
+        assert_eq!(
+            exec.plan(&engine, "TST", t0 + Duration::from_secs(7)),
+            vec![ExecAction::Cancel { order_id: child }]
+        );
+    }
+
+    #[test]
+    fn other_orders_on_the_symbol_are_not_children() {
+        let mut engine = OmsEngine::new();
+        let mut exec = Executor::new();
+        exec.set_params("TST", None, Some(params()));
+        exec.on_book(&book(dec!(100), dec!(102)));
+
+        // a strategy quote resting on the same symbol
+        let quote = engine.insert_order(
+            Order::new("TST", Side::Sell, dec!(1), dec!(105)).with_tag(Some("mm".into())),
+        );
+        engine.on_order_accepted(quote).unwrap();
+
+        // the quote counts in the delta but is neither cancelled nor amended
+        engine.set_target_position("TST", dec!(1));
+        assert_eq!(engine.delta("TST"), dec!(2));
+        assert_eq!(placed(exec.plan(&engine, "TST", Instant::now())), place(Side::Buy, dec!(2), dec!(101)));
+
+        engine.set_target_position("TST", dec!(-3));
+        assert_eq!(placed(exec.plan(&engine, "TST", Instant::now())), place(Side::Sell, dec!(2), dec!(101)));
+    }
+
+    #[test]
+    fn on_grid_child_is_not_repriced_on_a_still_book() {
+        let mut engine = OmsEngine::new();
+        engine.set_instruments(std::sync::Arc::new([Instrument::perp("TST", 2)].into_iter().collect()));
+        engine.set_target_position("TST", dec!(1));
+
+        let mut exec = Executor::new();
+        exec.set_params("TST", None, Some(params()));
+        // mid is 100.0015, off the 0.01 grid
+        exec.on_book(&book(dec!(100), dec!(100.003)));
+
+        let t0 = Instant::now();
+        let actions = exec.plan(&engine, "TST", t0);
+        assert_eq!(placed(actions.clone()), place(Side::Buy, dec!(1), dec!(100)));
+        start_child(&mut engine, actions);
+
+        assert!(exec.plan(&engine, "TST", t0).is_empty());
+        assert!(exec.plan(&engine, "TST", t0 + Duration::from_secs(60)).is_empty());
+    }
+}
@@ -1,23 +1,75 @@
 use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::order::OrderId;
 
-#[derive(Debug, Clone)]
+/// Which side of the trade we were on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Liquidity {
+    /// Our order was resting and got hit
+    Maker,
+
+    /// Our order crossed the book
+    #[default]
+    Taker,
+}
+
+/// One execution as reported by the venue.
+///
+/// Everything past `price` defaults when missing, so journals written before
+/// fees were recorded still replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Fill {
     pub order_id: OrderId,
-    pub qty: Decimal,      // signed
+    pub qty: Decimal,      // unsigned, direction comes from the order
     pub price: Decimal,    // execution price
-    pub fill_id: Uuid,
+
+    /// Positive is paid, negative is a rebate
+    #[serde(default)]
+    pub fee: Decimal,
+    #[serde(default)]
+    pub fee_currency: String,
+    #[serde(default)]
+    pub liquidity: Liquidity,
+
+    /// Venue trade id
+    #[serde(default)]
+    pub trade_id: String,
+
+    /// Venue time, ms since epoch
+    #[serde(default)]
+    pub time_ms: u64,
 }
 
 impl Fill {
+    /// A fill with no fee, for venues and tests that do not report one
     pub fn new(order_id: OrderId, qty: Decimal, price: Decimal) -> Self {
         Self {
             order_id,
             qty,
             price,
-            fill_id: Uuid::new_v4(),
+            fee: dec!(0),
+            fee_currency: String::new(),
+            liquidity: Liquidity::default(),
+            trade_id: Uuid::new_v4().to_string(),
+            time_ms: 0,
         }
     }
+
+    pub fn with_fee(mut self, fee: Decimal, currency: &str) -> Self {
+        self.fee = fee;
+        self.fee_currency = currency.to_string();
+        self
+    }
+
+    pub fn with_liquidity(mut self, liquidity: Liquidity) -> Self {
+        self.liquidity = liquidity;
+        self
+    }
+
+    pub fn notional(&self) -> Decimal {
+        self.qty * self.price
+    }
 }
@@ -0,0 +1,287 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use uuid::Uuid;
+
+use super::account::AccountSnapshot;
+use super::error::{OmsAnomaly, OmsError};
+use super::event::{FlattenLeg, OmsEvent};
+use super::executor::ExecParams;
+use super::ledger::LotRecord;
+use super::order::{OrderFlags, OrderId, Side, TimeInForce};
+use super::snapshot::{OmsSnapshot, OrderView};
+use super::update::OmsUpdate;
+
+/// How long a call waits for the OMS unless told otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A limit order as a strategy asks for it
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    pub symbol: String,
+    pub side: Side,
+    pub qty: Decimal,
+    pub price: Decimal,
+    pub tif: TimeInForce,
+    pub flags: OrderFlags,
+    pub tag: Option<String>,
+}
+
+impl OrderRequest {
+    pub fn limit(symbol: &str, side: Side, qty: Decimal, price: Decimal) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            side,
+            qty,
+            price,
+            tif: TimeInForce::default(),
+            flags: OrderFlags::default(),
+            tag: None,
+        }
+    }
+
+    pub fn with_tif(mut self, tif: TimeInForce) -> Self {
+        self.tif = tif;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: OrderFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+}
+
+/// Typed client for the OMS event loop.
+///
+/// Every call is bounded by the handle's timeout, so a stalled or stopped
+/// OMS surfaces as an error instead of a hung task. A timeout does not undo
+/// the call: see `OmsError::Timeout`.
+#[derive(Debug, Clone)]
+pub struct OmsHandle {
+    tx: mpsc::Sender<OmsEvent>,
+    updates: broadcast::Sender<OmsUpdate>,
+    timeout: Duration,
+}
+
+impl OmsHandle {
+    pub fn new(tx: mpsc::Sender<OmsEvent>, updates: broadcast::Sender<OmsUpdate>) -> Self {
+        Self { tx, updates, timeout: DEFAULT_TIMEOUT }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Pushed OMS changes; see `OmsUpdate`
+    pub fn subscribe(&self) -> broadcast::Receiver<OmsUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Raw sender, for events without a typed call
+    pub fn sender(&self) -> mpsc::Sender<OmsEvent> {
+        self.tx.clone()
+    }
+
+    async fn bounded<T>(&self, fut: impl Future<Output = Result<T, OmsError>>) -> Result<T, OmsError> {
+        tokio::time::timeout(self.timeout, fut)
+            .await
+            .unwrap_or(Err(OmsError::Timeout(self.timeout)))
+    }
+
+    async fn send(&self, event: OmsEvent) -> Result<(), OmsError> {
+        self.bounded(async { self.tx.send(event).await.map_err(|_| OmsError::Closed) })
+            .await
+    }
+
+    async fn ask<T>(&self, event: impl FnOnce(oneshot::Sender<T>) -> OmsEvent) -> Result<T, OmsError> {
+        let (reply, rx) = oneshot::channel();
+        let event = event(reply);
+        self.bounded(async {
+            self.tx.send(event).await.map_err(|_| OmsError::Closed)?;
+            rx.await.map_err(|_| OmsError::Closed)
+        })
+        .await
+    }
+
+    /// Venue-level delta for `symbol`, netted over every strategy
+    pub async fn delta(&self, symbol: &str) -> Result<Decimal, OmsError> {
+        self.ask(|reply| OmsEvent::GetDelta { symbol: symbol.to_string(), reply }).await
+    }
+
+    pub async fn snapshot(&self) -> Result<OmsSnapshot, OmsError> {
+        self.ask(|reply| OmsEvent::GetSnapshot { reply }).await
+    }
+
+    /// Most recent events the OMS refused to apply, oldest first
+    pub async fn quarantine(&self) -> Result<Vec<OmsAnomaly>, OmsError> {
+        self.ask(|reply| OmsEvent::GetQuarantine { reply }).await
+    }
+
+    /// `NotReady` until the first account poll has landed
+    pub async fn account(&self) -> Result<AccountSnapshot, OmsError> {
+        self.ask(|reply| OmsEvent::GetAccountSnapshot { reply })
+            .await?
+            .ok_or(OmsError::NotReady("account snapshot"))
+    }
+
+    pub async fn set_target(
+        &self,
+        symbol: &str,
+        qty: Decimal,
+        exec: Option<ExecParams>,
+        strategy: Option<&str>,
+    ) -> Result<(), OmsError> {
+        self.send(OmsEvent::SetTarget {
+            symbol: symbol.to_string(),
+            qty,
+            exec,
+            strategy: strategy.map(str::to_string),
+        })
+        .await
+    }
+
+    /// Id of the order once the OMS has taken it. The id is picked here,
+    /// so a timeout comes back as `Unconfirmed` with the id to look up.
+    pub async fn place(&self, order: OrderRequest) -> Result<OrderId, OmsError> {
+        let order_id = OrderId(Uuid::new_v4());
+        self.ask(|reply| OmsEvent::CreateOrder {
+            order_id,
+            symbol: order.symbol,
+            side: order.side,
+            qty: order.qty,
+            price: order.price,
+            tif: order.tif,
+            flags: order.flags,
+            tag: order.tag,
+            reply: Some(reply),
+        })
+        .await
+        .map_err(|e| match e {
+            OmsError::Timeout(_) => OmsError::Unconfirmed(order_id),
+            e => e,
+        })?
+    }
+
+    /// What became of an order, e.g. after `place` came back `Unconfirmed`
+    pub async fn order(&self, order_id: OrderId) -> Result<Option<OrderView>, OmsError> {
+        self.ask(|reply| OmsEvent::GetOrder { order_id, reply }).await
+    }
+
+    /// Realized PnL lot by lot, for reporting and tax exports. Empty under
+    /// `CostBasis::Average`.
+    pub async fn closed_lots(&self, symbol: Option<&str>) -> Result<Vec<LotRecord>, OmsError> {
+        let symbol = symbol.map(str::to_string);
+        self.ask(|reply| OmsEvent::GetClosedLots { symbol, reply }).await?
+    }
+
+    /// Queues every order before waiting on any, so the OMS can hand them
+    /// to the venue as one batch. Results are in request order.
+    pub async fn place_all(&self, orders: Vec<OrderRequest>) -> Vec<Result<OrderId, OmsError>> {
+        futures::future::join_all(orders.into_iter().map(|order| self.place(order))).await
+    }
+
+    pub async fn amend(&self, order_id: OrderId, price: Decimal, qty: Decimal) -> Result<(), OmsError> {
+        self.send(OmsEvent::AmendOrder { order_id, price, qty }).await
+    }
+
+    /// Cancels live orders, narrowed to `symbol` and/or `tag` when given
+    pub async fn cancel_all(&self, symbol: Option<&str>, tag: Option<&str>) -> Result<(), OmsError> {
+        self.send(OmsEvent::CancelAll {
+            symbol: symbol.map(str::to_string),
+            tag: tag.map(str::to_string),
+        })
+        .await
+    }
+
+    pub async fn pause(&self, reason: &str) -> Result<(), OmsError> {
+        self.send(OmsEvent::PauseTrading { reason: reason.to_string() }).await
+    }
+
+    pub async fn resume(&self, reason: &str) -> Result<(), OmsError> {
+        self.send(OmsEvent::ResumeTrading { reason: reason.to_string() }).await
+    }
+
+    pub async fn reduce_only(&self, reason: &str) -> Result<(), OmsError> {
+        self.send(OmsEvent::ReduceOnly { reason: reason.to_string() }).await
+    }
+
+    /// Leaves `Halted` after a kill; see `OmsEvent::Rearm`
+    pub async fn rearm(&self, operator: &str, reason: &str) -> Result<(), OmsError> {
+        self.send(OmsEvent::Rearm {
+            operator: operator.to_string(),
+            reason: reason.to_string(),
+        })
+        .await
+    }
+
+    pub async fn risk_kill(&self, reason: String, legs: Vec<FlattenLeg>) -> Result<(), OmsError> {
+        self.send(OmsEvent::RiskKill { reason, legs }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oms::order::Order;
+
+    #[tokio::test]
+    async fn missing_account_is_not_ready() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let oms = OmsHandle::new(tx, broadcast::channel(1).0);
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if let OmsEvent::GetAccountSnapshot { reply } = event {
+                    let _ = reply.send(None);
+                }
+            }
+        });
+
+        assert_eq!(oms.account().await.unwrap_err(), OmsError::NotReady("account snapshot"));
+    }
+
+    #[tokio::test]
+    async fn silent_oms_times_out_and_dead_oms_is_closed() {
+        let (tx, rx) = mpsc::channel(8);
+        let oms = OmsHandle::new(tx, broadcast::channel(1).0).with_timeout(Duration::from_millis(20));
+
+        // receiver alive but nobody answers
+        assert_eq!(oms.delta("TST").await, Err(OmsError::Timeout(Duration::from_millis(20))));
+
+        drop(rx);
+        assert_eq!(oms.snapshot().await.unwrap_err(), OmsError::Closed);
+    }
+
+    #[tokio::test]
+    async fn unanswered_place_can_be_looked_up() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let oms = OmsHandle::new(tx, broadcast::channel(1).0).with_timeout(Duration::from_millis(20));
+
+        let order_id = match oms.place(OrderRequest::limit("TST", Side::Buy, Decimal::ONE, Decimal::ONE_HUNDRED)).await {
+            Err(OmsError::Unconfirmed(id)) => id,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        // the OMS got to it after all
+        match rx.recv().await {
+            Some(OmsEvent::CreateOrder { order_id: sent, .. }) => assert_eq!(sent, order_id),
+            other => panic!("unexpected {:?}", other),
+        }
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if let OmsEvent::GetOrder { order_id, reply } = event {
+                    let _ = reply.send(Some(Order::new("TST", Side::Buy, Decimal::ONE, Decimal::ONE_HUNDRED).with_id(order_id).view()));
+                }
+            }
+        });
+        assert_eq!(oms.order(order_id).await.unwrap().unwrap().id, order_id);
+    }
+}
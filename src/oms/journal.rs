@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::engine::OmsEngine;
+use super::fill::Fill;
+use super::order::{CancelRejectReason, Order, OrderId, RejectReason};
+use super::position::{ClosedLot, CostBasis, Position};
+use super::state::TradingState;
+
+const JOURNAL_FILE: &str = "oms.journal";
+const SNAPSHOT_FILE: &str = "oms.snapshot.json";
+
+/// Every state change the OMS makes, in the order it made them.
+///
+/// Replaying these through `OmsEngine::apply` on top of the last snapshot
+/// rebuilds the engine exactly as it was before the process went down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JournalEntry {
+    SetTarget {
+        symbol: String,
+        qty: Decimal,
+        /// `None` for the shared, unnamed target
+        #[serde(default)]
+        strategy: Option<String>,
+    },
+    OrderCreated { order: Order },
+    OrderSent { order_id: OrderId },
+    StatusUnknown { order_id: OrderId },
+    OrderAccepted { order_id: OrderId },
+    OrderRejected {
+        order_id: OrderId,
+        #[serde(default)]
+        reason: Option<RejectReason>,
+    },
+    Fill {
+        #[serde(flatten)]
+        fill: Fill,
+    },
+    AmendRequested { order_id: OrderId, price: Decimal, qty: Decimal },
+    AmendAccepted { order_id: OrderId },
+    AmendRejected { order_id: OrderId },
+    CancelRequested { order_id: OrderId },
+    CancelConfirmed { order_id: OrderId },
+    CancelRejected { order_id: OrderId, reason: CancelRejectReason },
+    OrderExpired { order_id: OrderId },
+    TradingState {
+        state: TradingState,
+        /// Why, for the audit trail
+        #[serde(default)]
+        reason: String,
+    },
+
+    /// Reconciliation: replace our copy of an order with the venue's view
+    OrderSynced { order: Order },
+
+    /// Reconciliation: take the venue position as filled truth
+    PositionSynced { symbol: String, net_qty: Decimal, avg_price: Decimal },
+
+    /// Drift correction: a fill that belongs to no order
+    AdjustmentFill { symbol: String, qty: Decimal, price: Decimal },
+
+    /// Opposing strategy targets matched against each other at `price`
+    /// instead of on the venue
+    InternalCross { symbol: String, price: Decimal },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    seq: u64,
+    entry: JournalEntry,
+}
+
+/// Compacted engine state. `last_seq` is the last journal record folded in,
+/// so records that survive a crash mid-compaction are not applied twice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub last_seq: u64,
+    pub orders: Vec<Order>,
+    /// Shared, unnamed target per symbol
+    pub targets: HashMap<String, Decimal>,
+    /// symbol → strategy → target
+    #[serde(default)]
+    pub strategy_targets: HashMap<String, HashMap<String, Decimal>>,
+    pub positions: HashMap<String, Position>,
+    /// tag → symbol → position
+    #[serde(default)]
+    pub tag_positions: HashMap<String, HashMap<String, Position>>,
+    pub trading_state: TradingState,
+}
+
+/// Append-only on-disk log of `JournalEntry`s plus a periodic snapshot.
+pub struct Journal {
+    journal_path: PathBuf,
+    snapshot_path: PathBuf,
+    file: File,
+    /// Appended since the last `sync`
+    unsynced: bool,
+    next_seq: u64,
+    since_compaction: usize,
+    compact_every: usize,
+    /// Lots closed while replaying, by seq, for the ledger to catch up on
+    replayed_lots: Vec<(u64, Vec<(String, ClosedLot)>)>,
+}
+
+impl Journal {
+    /// Opens (or creates) the journal in `dir` and rebuilds the engine from
+    /// the last snapshot plus every record written after it. Positions are
+    /// replayed under `cost_basis`.
+    pub fn recover(dir: &Path, compact_every: usize, cost_basis: CostBasis) -> io::Result<(Self, OmsEngine)> {
+        fs::create_dir_all(dir)?;
+
+        let journal_path = dir.join(JOURNAL_FILE);
+        let snapshot_path = dir.join(SNAPSHOT_FILE);
+
+        let (mut engine, last_seq) = match fs::read(&snapshot_path) {
+            Ok(bytes) => {
+                let snapshot: EngineSnapshot = serde_json::from_slice(&bytes)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let last_seq = snapshot.last_seq;
+                (OmsEngine::from_snapshot(snapshot), last_seq)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (OmsEngine::new(), 0),
+            Err(e) => return Err(e),
+        };
+        engine.set_cost_basis(cost_basis);
+
+        let mut next_seq = last_seq + 1;
+        let mut replayed = 0;
+        let mut replayed_lots = Vec::new();
+
+        if journal_path.exists() {
+            let mut reader = BufReader::new(File::open(&journal_path)?);
+            // end of the last whole record; anything after it is cut off
+            let mut good_len: u64 = 0;
+            let mut line = Vec::new();
+
+            loop {
+                line.clear();
+                let read = reader.read_until(b'\n', &mut line)?;
+                if read == 0 {
+                    break;
+                }
+
+                // every record is written with its newline in one go
+                let record = match line.strip_suffix(b"\n") {
+                    Some(bytes) if bytes.trim_ascii().is_empty() => {
+                        good_len += read as u64;
+                        continue;
+                    }
+                    Some(bytes) => serde_json::from_slice::<Record>(bytes).map_err(|e| e.to_string()),
+                    None => Err("missing newline".to_string()),
+                };
+                let record = match record {
+                    Ok(r) => r,
+                    Err(e) => {
+                        // a torn write can only be the tail — stop there
+                        warn!("[JOURNAL] stopping replay at unreadable record: {}", e);
+                        break;
+                    }
+                };
+                good_len += read as u64;
+
+                if record.seq <= last_seq {
+                    continue;
+                }
+
+                if let Err(e) = engine.apply(&record.entry) {
+                    // same outcome as when it was first applied live
+                    warn!("[JOURNAL] seq={} replayed with anomaly: {}", record.seq, e);
+                }
+                let lots = engine.take_closed_lots();
+                if !lots.is_empty() {
+                    replayed_lots.push((record.seq, lots));
+                }
+                next_seq = record.seq + 1;
+                replayed += 1;
+            }
+
+            // new records must not land on the end of a torn one
+            let file = OpenOptions::new().write(true).open(&journal_path)?;
+            if file.metadata()?.len() > good_len {
+                warn!("[JOURNAL] cutting torn tail after byte {}", good_len);
+                file.set_len(good_len)?;
+                file.sync_all()?;
+            }
+        }
+
+        info!(
+            "[JOURNAL] recovered from {:?}: snapshot seq={} replayed={}",
+            dir, last_seq, replayed
+        );
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&journal_path)?;
+
+        Ok((
+            Self {
+                journal_path,
+                snapshot_path,
+                file,
+                unsynced: false,
+                next_seq,
+                since_compaction: replayed,
+                compact_every,
+                replayed_lots,
+            },
+            engine,
+        ))
+    }
+
+    /// Appends one entry. Must be called before the entry is applied; it is
+    /// on disk once `sync` has returned.
+    pub fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        let record = Record {
+            seq: self.next_seq,
+            entry: entry.clone(),
+        };
+
+        let mut line = serde_json::to_vec(&record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        line.push(b'\n');
+
+        self.file.write_all(&line)?;
+        self.unsynced = true;
+
+        self.next_seq += 1;
+        self.since_compaction += 1;
+        Ok(())
+    }
+
+    /// Seq of the last entry appended or recovered
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// Lots closed by the records replayed on recovery, by seq
+    pub fn take_replayed_lots(&mut self) -> Vec<(u64, Vec<(String, ClosedLot)>)> {
+        std::mem::take(&mut self.replayed_lots)
+    }
+
+    /// Flushes everything appended so far to disk. One fsync covers a whole
+    /// OMS turn, and runs before the turn's broker commands leave.
+    pub fn sync(&mut self) -> io::Result<()> {
+        if !self.unsynced {
+            return Ok(());
+        }
+        self.file.sync_data()?;
+        self.unsynced = false;
+        Ok(())
+    }
+
+    pub fn should_compact(&self) -> bool {
+        self.since_compaction >= self.compact_every
+    }
+
+    /// Writes the engine state as the new snapshot and truncates the journal.
+    pub fn compact(&mut self, engine: &OmsEngine) -> io::Result<()> {
+        let snapshot = engine.to_snapshot(self.next_seq - 1);
+        let bytes = serde_json::to_vec(&snapshot)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        // write-then-rename so a crash never leaves a half-written snapshot
+        let tmp_path = self.snapshot_path.with_extension("tmp");
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            tmp.write_all(&bytes)?;
+            tmp.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.snapshot_path)?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.journal_path)?;
+        self.file.sync_all()?;
+        self.unsynced = false;
+
+        info!("[JOURNAL] compacted at seq={}", snapshot.last_seq);
+        self.since_compaction = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oms::order::{OrderState, Side};
+    use rust_decimal_macros::dec;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("laminar-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn commit(journal: &mut Journal, engine: &mut OmsEngine, entry: JournalEntry) {
+        journal.append(&entry).unwrap();
+        engine.apply(&entry).unwrap();
+    }
+
+    #[test]
+    fn replay_restores_orders_and_pnl() {
+        let dir = temp_dir("replay");
+        let (mut journal, mut engine) = Journal::recover(&dir, 1_000, CostBasis::Average).unwrap();
+
+        let order = Order::new("TST", Side::Buy, dec!(2), dec!(100));
+        let oid = order.id;
+
+        commit(&mut journal, &mut engine, JournalEntry::SetTarget { symbol: "TST".into(), qty: dec!(2), strategy: None });
+        commit(&mut journal, &mut engine, JournalEntry::OrderCreated { order });
+        commit(&mut journal, &mut engine, JournalEntry::OrderAccepted { order_id: oid });
+        commit(&mut journal, &mut engine, JournalEntry::Fill { fill: Fill::new(oid, dec!(1), dec!(100)) });
+
+        let sell = Order::new("TST", Side::Sell, dec!(1), dec!(110));
+        let sid = sell.id;
+        commit(&mut journal, &mut engine, JournalEntry::OrderCreated { order: sell });
+        commit(&mut journal, &mut engine, JournalEntry::Fill { fill: Fill::new(sid, dec!(1), dec!(110)) });
+        drop(journal);
+
+        let (_, recovered) = Journal::recover(&dir, 1_000, CostBasis::Average).unwrap();
+        let pos = recovered.position("TST").unwrap();
+        assert_eq!(pos.net_qty, dec!(0));
+        assert_eq!(pos.realized_pnl, dec!(10));
+        assert_eq!(recovered.delta("TST"), engine.delta("TST"));
+        assert_eq!(recovered.order_views().len(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn compaction_survives_restart() {
+        let dir = temp_dir("compact");
+        let (mut journal, mut engine) = Journal::recover(&dir, 2, CostBasis::Average).unwrap();
+
+        let order = Order::new("TST", Side::Sell, dec!(3), dec!(50));
+        let oid = order.id;
+
+        commit(&mut journal, &mut engine, JournalEntry::OrderCreated { order });
+        commit(&mut journal, &mut engine, JournalEntry::OrderAccepted { order_id: oid });
+        assert!(journal.should_compact());
+        journal.compact(&engine).unwrap();
+
+        commit(&mut journal, &mut engine, JournalEntry::Fill { fill: Fill::new(oid, dec!(1), dec!(50)) });
+        drop(journal);
+
+        let (_, recovered) = Journal::recover(&dir, 2, CostBasis::Average).unwrap();
+        assert_eq!(recovered.position("TST").unwrap().net_qty, dec!(-1));
+        assert_eq!(recovered.delta("TST"), dec!(3));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn replay_hands_back_closed_lots_by_seq() {
+        let dir = temp_dir("replay-lots");
+        let (mut journal, mut engine) = Journal::recover(&dir, 1_000, CostBasis::Fifo).unwrap();
+
+        let buy = Order::new("TST", Side::Buy, dec!(2), dec!(100));
+        let bid = buy.id;
+        let sell = Order::new("TST", Side::Sell, dec!(2), dec!(110));
+        let sid = sell.id;
+        commit(&mut journal, &mut engine, JournalEntry::OrderCreated { order: buy });
+        commit(&mut journal, &mut engine, JournalEntry::Fill { fill: Fill::new(bid, dec!(2), dec!(100)) });
+        commit(&mut journal, &mut engine, JournalEntry::OrderCreated { order: sell });
+        commit(&mut journal, &mut engine, JournalEntry::Fill { fill: Fill::new(sid, dec!(1), dec!(110)) });
+        assert_eq!(engine.take_closed_lots().len(), 1);
+        assert!(engine.take_closed_lots().is_empty());
+        drop(journal);
+
+        let (mut journal, _) = Journal::recover(&dir, 1_000, CostBasis::Fifo).unwrap();
+        let replayed = journal.take_replayed_lots();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].0, journal.last_seq());
+        assert_eq!(replayed[0].1[0].0, "TST");
+        assert_eq!(replayed[0].1[0].1.realized_pnl, dec!(10));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn torn_tail_is_cut_before_appending() {
+        let dir = temp_dir("torn");
+        let (mut journal, mut engine) = Journal::recover(&dir, 1_000, CostBasis::Average).unwrap();
+
+        let order = Order::new("TST", Side::Buy, dec!(2), dec!(100));
+        let oid = order.id;
+        commit(&mut journal, &mut engine, JournalEntry::OrderCreated { order });
+        commit(&mut journal, &mut engine, JournalEntry::OrderAccepted { order_id: oid });
+        drop(journal);
+
+        // crash halfway through a record
+        let mut file = OpenOptions::new().append(true).open(dir.join(JOURNAL_FILE)).unwrap();
+        file.write_all(br#"{"seq":3,"entry":{"Fill":"#).unwrap();
+        drop(file);
+
+        let (mut journal, mut engine) = Journal::recover(&dir, 1_000, CostBasis::Average).unwrap();
+        commit(&mut journal, &mut engine, JournalEntry::Fill { fill: Fill::new(oid, dec!(1), dec!(100)) });
+        drop(journal);
+
+        // what was written after the first recovery survives the second
+        let (_, recovered) = Journal::recover(&dir, 1_000, CostBasis::Average).unwrap();
+        assert_eq!(recovered.position("TST").unwrap().net_qty, dec!(1));
+        assert_eq!(recovered.order(oid).unwrap().state, OrderState::PartiallyFilled { remaining: dec!(1) });
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::position::ClosedLot;
+
+const LEDGER_FILE: &str = "closed_lots.jsonl";
+
+/// One closed lot as the ledger keeps it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotRecord {
+    /// Journal seq of the entry that closed the lot
+    pub seq: u64,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub lot: ClosedLot,
+}
+
+/// Append-only file of every lot closed on account positions, for reporting
+/// and tax exports. Lives next to the journal but is never compacted, so
+/// nothing in it is lost when the journal is.
+pub struct LotLedger {
+    path: PathBuf,
+    file: File,
+    /// Highest seq on disk; entries at or below it are already recorded
+    last_seq: u64,
+    unsynced: bool,
+}
+
+impl LotLedger {
+    /// Opens (or creates) the ledger in `dir`, cutting a torn last line.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(LEDGER_FILE);
+
+        let mut last_seq = 0;
+        let mut count = 0;
+        if path.exists() {
+            let mut good_len: u64 = 0;
+            for line in read_lines(&path)? {
+                let Ok((read, record)) = line else {
+                    break;
+                };
+                good_len += read;
+                if let Some(record) = record {
+                    last_seq = last_seq.max(record.seq);
+                    count += 1;
+                }
+            }
+
+            let file = OpenOptions::new().write(true).open(&path)?;
+            if file.metadata()?.len() > good_len {
+                warn!("[LEDGER] cutting torn tail after byte {}", good_len);
+                file.set_len(good_len)?;
+                file.sync_all()?;
+            }
+        }
+
+        info!("[LEDGER] opened {:?}: {} closed lots, last seq={}", path, count, last_seq);
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, file, last_seq, unsynced: false })
+    }
+
+    /// Records the lots the entry at `seq` closed, all in one write. Entries
+    /// already on disk are skipped, so replayed lots can be offered again.
+    pub fn append(&mut self, seq: u64, lots: Vec<(String, ClosedLot)>) -> io::Result<Vec<LotRecord>> {
+        if seq <= self.last_seq || lots.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let records: Vec<LotRecord> = lots
+            .into_iter()
+            .map(|(symbol, lot)| LotRecord { seq, symbol, lot })
+            .collect();
+
+        let mut buf = Vec::new();
+        for record in &records {
+            serde_json::to_writer(&mut buf, record)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            buf.push(b'\n');
+        }
+
+        self.file.write_all(&buf)?;
+        self.unsynced = true;
+        self.last_seq = seq;
+        Ok(records)
+    }
+
+    pub fn sync(&mut self) -> io::Result<()> {
+        if !self.unsynced {
+            return Ok(());
+        }
+        self.file.sync_data()?;
+        self.unsynced = false;
+        Ok(())
+    }
+
+    /// Every recorded lot, oldest first, optionally for one symbol only
+    pub fn read(&self, symbol: Option<&str>) -> io::Result<Vec<LotRecord>> {
+        let mut records = Vec::new();
+        for line in read_lines(&self.path)? {
+            if let Some(record) = line?.1 {
+                if symbol.is_none_or(|s| s == record.symbol) {
+                    records.push(record);
+                }
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// Lines of the ledger with their length in bytes; blank lines carry no
+/// record. Stops with an error at a line that is torn or unreadable.
+fn read_lines(path: &Path) -> io::Result<impl Iterator<Item = io::Result<(u64, Option<LotRecord>)>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut done = false;
+
+    Ok(std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let mut line = Vec::new();
+        let read = match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return None,
+            Ok(n) => n as u64,
+            Err(e) => {
+                done = true;
+                return Some(Err(e));
+            }
+        };
+
+        let parsed = match line.strip_suffix(b"\n") {
+            Some(bytes) if bytes.trim_ascii().is_empty() => Ok(None),
+            Some(bytes) => serde_json::from_slice(bytes).map(Some).map_err(|e| e.to_string()),
+            None => Err("missing newline".to_string()),
+        };
+        Some(match parsed {
+            Ok(record) => Ok((read, record)),
+            Err(e) => {
+                done = true;
+                Err(io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("laminar-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn lot(symbol: &str, pnl: i64) -> (String, ClosedLot) {
+        let lot = ClosedLot {
+            qty: dec!(1),
+            open_price: dec!(100),
+            close_price: dec!(100) + rust_decimal::Decimal::from(pnl),
+            opened_ms: 1,
+            closed_ms: 2,
+            realized_pnl: pnl.into(),
+        };
+        (symbol.to_string(), lot)
+    }
+
+    #[test]
+    fn lots_survive_reopen_and_are_not_recorded_twice() {
+        let dir = temp_dir("ledger");
+        {
+            let mut ledger = LotLedger::open(&dir).unwrap();
+            let written = ledger.append(3, vec![lot("BTC", 5), lot("BTC", 7)]).unwrap();
+            assert_eq!(written.len(), 2);
+            ledger.append(4, vec![lot("ETH", -2)]).unwrap();
+            ledger.sync().unwrap();
+        }
+
+        let mut ledger = LotLedger::open(&dir).unwrap();
+        // replay offers the same entries again
+        assert!(ledger.append(4, vec![lot("ETH", -2)]).unwrap().is_empty());
+        ledger.append(9, vec![lot("ETH", 1)]).unwrap();
+
+        let all = ledger.read(None).unwrap();
+        assert_eq!(all.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![3, 3, 4, 9]);
+
+        let btc = ledger.read(Some("BTC")).unwrap();
+        assert_eq!(btc.len(), 2);
+        assert_eq!(btc[1].lot.realized_pnl, dec!(7));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn torn_line_is_cut_on_open() {
+        let dir = temp_dir("ledger-torn");
+        {
+            let mut ledger = LotLedger::open(&dir).unwrap();
+            ledger.append(1, vec![lot("BTC", 5)]).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(dir.join(LEDGER_FILE)).unwrap();
+        file.write_all(br#"{"seq":2,"symbol":"BT"#).unwrap();
+
+        let mut ledger = LotLedger::open(&dir).unwrap();
+        ledger.append(2, vec![lot("BTC", 3)]).unwrap();
+
+        let all = ledger.read(None).unwrap();
+        assert_eq!(all.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![1, 2]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::market::types::{MarkPrice, MarketSnapshot, Trade};
+
+/// Which market price open positions are marked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkSource {
+    /// Middle of the top of book
+    #[default]
+    Mid,
+
+    /// The venue's own mark price
+    Mark,
+
+    /// Last print on the tape
+    Last,
+}
+
+impl MarkSource {
+    pub fn from_book(self, snapshot: &MarketSnapshot) -> Option<Decimal> {
+        if self != MarkSource::Mid {
+            return None;
+        }
+        let bid = snapshot.book.bids.first()?.price;
+        let ask = snapshot.book.asks.first()?.price;
+        Some((bid + ask) / dec!(2))
+    }
+
+    pub fn from_trade(self, trade: &Trade) -> Option<Decimal> {
+        (self == MarkSource::Last).then_some(trade.price)
+    }
+
+    pub fn from_mark(self, mark: &MarkPrice) -> Option<Decimal> {
+        (self == MarkSource::Mark).then_some(mark.price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::types::{AggressorSide, BookLevel, OrderBook};
+
+    #[test]
+    fn only_the_configured_source_marks() {
+        let book = MarketSnapshot {
+            symbol: "TST".into(),
+            book: OrderBook {
+                bids: vec![BookLevel { price: dec!(100), qty: dec!(1) }],
+                asks: vec![BookLevel { price: dec!(102), qty: dec!(1) }],
+            },
+            timestamp_ms: 0,
+        };
+        let trade = Trade {
+            symbol: "TST".into(),
+            price: dec!(103),
+            qty: dec!(1),
+            side: AggressorSide::Buy,
+            timestamp_ms: 0,
+        };
+        let mark = MarkPrice { symbol: "TST".into(), price: dec!(99), timestamp_ms: 0 };
+
+        assert_eq!(MarkSource::Mid.from_book(&book), Some(dec!(101)));
+        assert_eq!(MarkSource::Mid.from_trade(&trade), None);
+        assert_eq!(MarkSource::Last.from_trade(&trade), Some(dec!(103)));
+        assert_eq!(MarkSource::Last.from_book(&book), None);
+        assert_eq!(MarkSource::Mark.from_mark(&mark), Some(dec!(99)));
+        assert_eq!(MarkSource::Mark.from_book(&book), None);
+
+        // one-sided book has no mid
+        let mut empty = book.clone();
+        empty.book.asks.clear();
+        assert_eq!(MarkSource::Mid.from_book(&empty), None);
+    }
+}
@@ -1,6 +1,7 @@
 
 pub mod core;
 pub mod order;
+pub mod error;
 pub mod engine;
 pub mod fill;
 pub mod position;
@@ -9,3 +10,14 @@ pub mod runtime;
 pub mod snapshot;
 pub mod account;
 pub mod state;
+pub mod journal;
+pub mod ledger;
+pub mod config;
+pub mod reconcile;
+pub mod drift;
+pub mod executor;
+pub mod algo;
+pub mod mark;
+pub mod handle;
+pub mod update;
+pub mod outbox;
@@ -1,8 +1,13 @@
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use super::error::OrderError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct OrderId(pub Uuid);
 use std::fmt;
 
@@ -14,7 +19,7 @@ impl fmt::Display for OrderId {
 
 // pub type ClientOrderId = String;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Side {
     Buy,
     Sell,
@@ -29,11 +34,37 @@ impl Side {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Rest until filled or cancelled
+    #[default]
+    Gtc,
+
+    /// Take whatever crosses now, cancel the rest
+    Ioc,
+
+    /// Post-only ("add liquidity only") — rejected rather than crossing
+    Alo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OrderFlags {
+    /// Only ever shrink the position; venue trims or rejects otherwise
+    pub reduce_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderState {
     /// Created internally, not yet sent
     New,
 
+    /// Sent to the venue, no ack yet
+    PendingAck,
+
+    /// Ack never came (timeout or transport failure). The order may or may
+    /// not be live; the broker is asked for its status by cloid.
+    Unknown,
+
     /// Accepted by venue, live
     Open { remaining: Decimal },
 
@@ -43,60 +74,226 @@ pub enum OrderState {
     /// Fully filled
     Filled,
 
-    /// Cancel requested
-    CancelPending,
+    /// Amend sent, venue has not answered yet. `remaining` is still live at
+    /// the old price until the replace is confirmed.
+    ReplacePending {
+        remaining: Decimal,
+        new_price: Decimal,
+        new_qty: Decimal,
+    },
+
+    /// Cancel sent, venue has not answered yet. `remaining` is still live
+    /// until the cancel is confirmed.
+    CancelPending { remaining: Decimal },
 
     /// Cancel confirmed
     Cancelled,
 
-    /// Rejected by venue
+    /// Rejected by the venue, or locally before it was sent
     Rejected,
 }
 
-#[derive(Debug, Clone)]
+/// Why an order never went live
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RejectReason {
+    /// No instrument for the symbol on this network
+    UnknownSymbol,
+
+    /// Limit price is zero or negative
+    NonPositivePrice,
+
+    /// Nothing left once the size is rounded down to whole lots
+    ZeroQty { requested: Decimal },
+
+    /// Order value under the venue minimum
+    BelowMinNotional { notional: Decimal, min: Decimal },
+
+    /// Venue: not enough collateral for the order
+    InsufficientMargin,
+
+    /// Venue: a post-only order would have taken liquidity
+    PostOnlyWouldCross,
+
+    /// Venue: price too far from the reference price
+    PriceOutOfBand,
+
+    /// Venue: request or volume-based rate limit hit
+    RateLimited,
+
+    /// Venue refusal with no matching variant, as the venue worded it
+    Unknown(String),
+}
+
+impl RejectReason {
+    /// Refused by the venue rather than by local validation
+    pub fn is_venue(&self) -> bool {
+        !matches!(
+            self,
+            RejectReason::UnknownSymbol
+                | RejectReason::NonPositivePrice
+                | RejectReason::ZeroQty { .. }
+                | RejectReason::BelowMinNotional { .. }
+        )
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::UnknownSymbol => write!(f, "unknown symbol"),
+            RejectReason::NonPositivePrice => write!(f, "price must be positive"),
+            RejectReason::ZeroQty { requested } => write!(f, "size {} is below one lot", requested),
+            RejectReason::BelowMinNotional { notional, min } => {
+                write!(f, "notional {} is below the minimum {}", notional, min)
+            }
+            RejectReason::InsufficientMargin => write!(f, "insufficient margin"),
+            RejectReason::PostOnlyWouldCross => write!(f, "post-only order would cross"),
+            RejectReason::PriceOutOfBand => write!(f, "price out of band"),
+            RejectReason::RateLimited => write!(f, "rate limited"),
+            RejectReason::Unknown(msg) => write!(f, "venue: {}", msg),
+        }
+    }
+}
+
+/// Why the venue did not cancel an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CancelRejectReason {
+    /// Order traded out before the cancel landed
+    AlreadyFilled,
+
+    /// Venue has no such order (never placed, or already gone)
+    UnknownOrder,
+
+    /// Venue refused but the order is still resting
+    Refused(String),
+
+    /// Cancel or status query never got an answer
+    Transport(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: OrderId,              // internal
     // pub cloid: ClientOrderId,     // external
+    pub symbol: String,
     pub side: Side,
     pub original_qty: Decimal,
     pub limit_price: Decimal,
+    #[serde(default)]
+    pub tif: TimeInForce,
+    #[serde(default)]
+    pub flags: OrderFlags,
+    /// Owning strategy, for attribution and scoped cancels
+    #[serde(default)]
+    pub tag: Option<String>,
     pub state: OrderState,
+    /// Set once the order is rejected, if the rejecter said why
+    #[serde(default)]
+    pub reject_reason: Option<RejectReason>,
+    /// Venue trade ids already booked, so a redelivered fill is not booked
+    /// twice
+    #[serde(default)]
+    pub trade_ids: HashSet<String>,
 }
 
 impl Order {
-    pub fn new(side: Side, qty: Decimal, price: Decimal) -> Self {
+    pub fn new(symbol: &str, side: Side, qty: Decimal, price: Decimal) -> Self {
         Self {
             id: OrderId(Uuid::new_v4()),
             // cloid: format!("laminar-{}", Uuid::new_v4()),
+            symbol: symbol.to_string(),
             side,
             original_qty: qty,
             limit_price: price,
+            tif: TimeInForce::Gtc,
+            flags: OrderFlags::default(),
+            tag: None,
             state: OrderState::New,
+            reject_reason: None,
+            trade_ids: HashSet::new(),
         }
     }
 
+    pub fn with_id(mut self, id: OrderId) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn with_tif(mut self, tif: TimeInForce) -> Self {
+        self.tif = tif;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: OrderFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: Option<String>) -> Self {
+        self.tag = tag;
+        self
+    }
+
     pub fn view(&self) -> super::snapshot::OrderView {
         let remaining_qty = match self.state {
             OrderState::Open { remaining }
-            | OrderState::PartiallyFilled { remaining } => remaining,
+            | OrderState::PartiallyFilled { remaining }
+            | OrderState::ReplacePending { remaining, .. }
+            | OrderState::CancelPending { remaining } => remaining,
+
+            OrderState::PendingAck | OrderState::Unknown => self.original_qty,
 
             _ => dec!(0),
         };
 
         super::snapshot::OrderView {
             id: self.id,
+            symbol: self.symbol.clone(),
             side: self.side,
             limit_price: self.limit_price,
+            tif: self.tif,
+            flags: self.flags,
+            tag: self.tag.clone(),
             original_qty: self.original_qty,
             remaining_qty,
             state: self.state.clone(),
+            reject_reason: self.reject_reason.clone(),
         }
     }
 
+    /// Called when the order is handed to the broker
+    pub fn on_sent(&mut self) -> Result<(), OrderError> {
+        match self.state {
+            OrderState::New => {
+                self.state = OrderState::PendingAck;
+                Ok(())
+            }
+            _ => Err(self.invalid("send")),
+        }
+    }
+
+    /// Called when the ack deadline passes or the send failed in transit
+    pub fn on_status_unknown(&mut self) {
+        if matches!(self.state, OrderState::New | OrderState::PendingAck) {
+            self.state = OrderState::Unknown;
+        }
+        // otherwise the venue answered in the meantime
+    }
+
+    /// Filled, cancelled or rejected: nothing more will happen to it
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.state, OrderState::Filled | OrderState::Cancelled | OrderState::Rejected)
+    }
+
+    /// True while we are waiting to hear whether the venue has the order
+    pub fn is_unacked(&self) -> bool {
+        matches!(self.state, OrderState::PendingAck | OrderState::Unknown)
+    }
+
     /// Called when venue acks the order
     pub fn on_accepted(&mut self) {
         match self.state {
-            OrderState::New => {
+            OrderState::New | OrderState::PendingAck | OrderState::Unknown => {
                 self.state = OrderState::Open {
                     remaining: self.original_qty,
                 };
@@ -105,85 +302,244 @@ impl Order {
             // Accept arrived late — already progressed
             OrderState::Open { .. }
             | OrderState::PartiallyFilled { .. }
+            | OrderState::ReplacePending { .. }
             | OrderState::Filled
                 | OrderState::Cancelled
-                | OrderState::CancelPending
+                | OrderState::CancelPending { .. }
                 | OrderState::Rejected => {
                     // Idempotent / out-of-order accept
-                    return;
                 }
         }
     }
 
-    pub fn on_rejected(&mut self) {
+    fn invalid(&self, event: &'static str) -> OrderError {
+        OrderError::InvalidTransition {
+            order_id: self.id,
+            from: self.state.clone(),
+            event,
+        }
+    }
+
+    pub fn on_rejected(&mut self, reason: Option<RejectReason>) -> Result<(), OrderError> {
         match self.state {
-            OrderState::New => {
+            OrderState::New | OrderState::PendingAck | OrderState::Unknown => {
                 self.state = OrderState::Rejected;
+                self.reject_reason = reason;
+                Ok(())
             }
-            _ => panic!("reject on non-created order"),
+            // duplicate reject
+            OrderState::Rejected => Ok(()),
+            _ => Err(self.invalid("reject")),
         }
     }
 
+    /// Remembers a trade id; false if it was booked before. Fills without
+    /// an id (older journals) cannot be told apart and always count.
+    pub fn record_trade(&mut self, trade_id: &str) -> bool {
+        trade_id.is_empty() || self.trade_ids.insert(trade_id.to_string())
+    }
+
     /// Called when a fill arrives
-    pub fn on_fill(&mut self, fill_qty: Decimal) {
+    pub fn on_fill(&mut self, fill_qty: Decimal) -> Result<(), OrderError> {
+        if fill_qty <= dec!(0) {
+            return Err(OrderError::NonPositiveFill {
+                order_id: self.id,
+                qty: fill_qty,
+            });
+        }
+
         let remaining = match self.state {
             OrderState::Open { remaining }
-            | OrderState::PartiallyFilled { remaining } => remaining,
-            OrderState::New=> {
-                // Treat fill as implicit acceptance
-                self.on_accepted();
-                match self.state {
-                    OrderState::Open { remaining } => remaining,
-                    _ => unreachable!(),
-                }
+            | OrderState::PartiallyFilled { remaining }
+            | OrderState::ReplacePending { remaining, .. }
+            | OrderState::CancelPending { remaining } => remaining,
+
+            // Treat fill as implicit acceptance
+            OrderState::New | OrderState::PendingAck | OrderState::Unknown => self.original_qty,
+
+            // nothing was left: the venue traded more than we ordered
+            OrderState::Filled => {
+                return Err(OrderError::Overfill {
+                    order_id: self.id,
+                    remaining: dec!(0),
+                    fill_qty,
+                });
             }
 
-            OrderState::Filled
-                | OrderState::Cancelled
-                | OrderState::CancelPending
-                | OrderState::Rejected => {
-                // Idempotency / late WS message
-                return;
-            }
+            // late fill that beat the cancel or reject; the venue is right
+            OrderState::Cancelled | OrderState::Rejected => return Ok(()),
         };
-        // _ => panic!("Fill on non-live order"),
-        // };
 
-        assert!(fill_qty > dec!(0));
-        assert!(fill_qty <= remaining);
+        if fill_qty > remaining {
+            // venue says more traded than we had out; nothing is left either way
+            self.state = OrderState::Filled;
+            return Err(OrderError::Overfill {
+                order_id: self.id,
+                remaining,
+                fill_qty,
+            });
+        }
 
         let new_remaining = remaining - fill_qty;
 
-        self.state = if new_remaining == dec!(0) {
-            OrderState::Filled
-        } else {
-            OrderState::PartiallyFilled {
+        self.state = match self.state {
+            _ if new_remaining == dec!(0) => OrderState::Filled,
+
+            // keep the amend in flight; the old order is what got hit
+            OrderState::ReplacePending { new_price, new_qty, .. } => {
+                OrderState::ReplacePending {
+                    remaining: new_remaining,
+                    new_price,
+                    new_qty,
+                }
+            }
+
+            // the cancel may still lose the race; what is left stays live
+            OrderState::CancelPending { .. } => OrderState::CancelPending {
+                remaining: new_remaining,
+            },
+
+            _ => OrderState::PartiallyFilled {
                 remaining: new_remaining,
+            },
+        };
+        Ok(())
+    }
+
+    pub fn on_cancel_requested(&mut self) -> Result<(), OrderError> {
+        match self.state {
+            // may be live; cancel by cloid works whether or not it was acked
+            OrderState::PendingAck | OrderState::Unknown => {
+                self.state = OrderState::CancelPending { remaining: self.original_qty };
+                Ok(())
+            }
+            OrderState::Open { remaining }
+            | OrderState::PartiallyFilled { remaining }
+            | OrderState::ReplacePending { remaining, .. } => {
+                self.state = OrderState::CancelPending { remaining };
+                Ok(())
+            }
+            _ => Err(self.invalid("cancel request")),
+        }
+    }
+
+    /// Called when the venue did not cancel. The order either goes back to
+    /// being live or resolves to whatever the venue says became of it. An
+    /// order that traded out stays CancelPending until its fills are booked,
+    /// so its exposure does not drop before the position moves.
+    pub fn on_cancel_rejected(&mut self, reason: &CancelRejectReason) -> Result<(), OrderError> {
+        let remaining = match self.state {
+            OrderState::CancelPending { remaining } => remaining,
+            // resolved by a fill or confirm that raced the reject
+            OrderState::Filled | OrderState::Cancelled => return Ok(()),
+            _ => return Err(self.invalid("cancel reject")),
+        };
+
+        self.state = match reason {
+            CancelRejectReason::AlreadyFilled => return Ok(()),
+            CancelRejectReason::UnknownOrder => OrderState::Cancelled,
+            CancelRejectReason::Refused(_) | CancelRejectReason::Transport(_) => {
+                self.live_state(remaining)
             }
         };
+        Ok(())
     }
 
-    pub fn on_cancel_requested(&mut self) {
+    /// Called when an amend is sent. `new_qty` is the size the order should
+    /// rest with after the replace.
+    pub fn on_amend_requested(&mut self, new_price: Decimal, new_qty: Decimal) -> Result<(), OrderError> {
         match self.state {
-            OrderState::Open { .. }
-            | OrderState::PartiallyFilled { .. } => {
-                self.state = OrderState::CancelPending;
+            OrderState::Open { remaining }
+            | OrderState::PartiallyFilled { remaining } => {
+                self.state = OrderState::ReplacePending {
+                    remaining,
+                    new_price,
+                    new_qty,
+                };
+                Ok(())
             }
-            _ => panic!("Cancel requested on non-cancellable order"),
+            _ => Err(self.invalid("amend request")),
+        }
+    }
+
+    /// Called when venue confirms the replace
+    pub fn on_amend_accepted(&mut self) {
+        if let OrderState::ReplacePending { remaining, new_price, new_qty } = self.state {
+            let filled = self.original_qty - remaining;
+
+            self.limit_price = new_price;
+            self.original_qty = filled + new_qty;
+            self.state = if filled == dec!(0) {
+                OrderState::Open { remaining: new_qty }
+            } else {
+                OrderState::PartiallyFilled { remaining: new_qty }
+            };
+        }
+        // otherwise filled or cancelled while the amend was in flight
+    }
+
+    /// Called when venue refuses the replace — the old order stands
+    pub fn on_amend_rejected(&mut self) {
+        if let OrderState::ReplacePending { remaining, .. } = self.state {
+            self.state = self.live_state(remaining);
         }
     }
 
-    pub fn on_cancel_confirmed(&mut self) {
-        assert!(matches!(self.state, OrderState::CancelPending));
-        self.state = OrderState::Cancelled;
+    /// Takes price, size and state from what the venue reports for a
+    /// resting order, whatever we thought before
+    pub fn sync_resting(&mut self, price: Decimal, original_qty: Decimal, remaining: Decimal) {
+        self.limit_price = price;
+        self.original_qty = original_qty;
+        self.state = self.live_state(remaining);
+    }
+
+    fn live_state(&self, remaining: Decimal) -> OrderState {
+        if remaining == self.original_qty {
+            OrderState::Open { remaining }
+        } else {
+            OrderState::PartiallyFilled { remaining }
+        }
+    }
+
+    /// Venue closed the order without us asking, e.g. the unfilled
+    /// remainder of an IOC. Late fills still reach the position.
+    pub fn on_expired(&mut self) {
+        match self.state {
+            OrderState::Filled
+            | OrderState::Cancelled
+            | OrderState::Rejected => {}
+            _ => self.state = OrderState::Cancelled,
+        }
+    }
+
+    pub fn on_cancel_confirmed(&mut self) -> Result<(), OrderError> {
+        match self.state {
+            OrderState::CancelPending { .. } => {
+                self.state = OrderState::Cancelled;
+                Ok(())
+            }
+            // duplicate confirm
+            OrderState::Cancelled => Ok(()),
+            _ => Err(self.invalid("cancel confirm")),
+        }
     }
 
     pub fn remaining_signed_qty(&self) -> Decimal {
         match self.state {
             OrderState::Open { remaining }
-            | OrderState::PartiallyFilled { remaining } => {
+            | OrderState::PartiallyFilled { remaining }
+            | OrderState::CancelPending { remaining } => {
                 remaining * self.side.sign()
             }
+            // either the old or the new size can end up live — count the
+            // larger so nobody sends more while the amend is in flight
+            OrderState::ReplacePending { remaining, new_qty, .. } => {
+                remaining.max(new_qty) * self.side.sign()
+            }
+            // assume live until the venue says otherwise
+            OrderState::PendingAck | OrderState::Unknown => {
+                self.original_qty * self.side.sign()
+            }
             _ => dec!(0),
         }
     }
@@ -196,27 +552,150 @@ mod tests {
 
     #[test]
     fn partial_fill_flow() {
-        let mut o = Order::new(Side::Buy, dec!(1.0), dec!(100));
+        let mut o = Order::new("TST", Side::Buy, dec!(1.0), dec!(100));
         o.on_accepted();
 
-        o.on_fill(dec!(0.4));
+        o.on_fill(dec!(0.4)).unwrap();
         assert_eq!(
             o.state,
             OrderState::PartiallyFilled { remaining: dec!(0.6) }
         );
 
-        o.on_fill(dec!(0.6));
+        o.on_fill(dec!(0.6)).unwrap();
         assert_eq!(o.state, OrderState::Filled);
     }
 
     #[test]
     fn cancel_flow() {
-        let mut o = Order::new(Side::Sell, dec!(2.0), dec!(101));
+        let mut o = Order::new("TST", Side::Sell, dec!(2.0), dec!(101));
         o.on_accepted();
-        o.on_cancel_requested();
-        o.on_cancel_confirmed();
+        o.on_cancel_requested().unwrap();
+        o.on_cancel_confirmed().unwrap();
 
         assert_eq!(o.state, OrderState::Cancelled);
     }
 
+    #[test]
+    fn amend_flow() {
+        let mut o = Order::new("TST", Side::Buy, dec!(2.0), dec!(100));
+        o.on_accepted();
+        o.on_fill(dec!(0.5)).unwrap();
+
+        o.on_amend_requested(dec!(101), dec!(3.0)).unwrap();
+        assert_eq!(o.remaining_signed_qty(), dec!(3.0));
+
+        // old order keeps trading while the amend is in flight
+        o.on_fill(dec!(0.5)).unwrap();
+        assert_eq!(o.remaining_signed_qty(), dec!(3.0));
+
+        o.on_amend_accepted();
+        assert_eq!(o.limit_price, dec!(101));
+        assert_eq!(o.original_qty, dec!(4.0));
+        assert_eq!(o.state, OrderState::PartiallyFilled { remaining: dec!(3.0) });
+    }
+
+    #[test]
+    fn ioc_remainder_expires() {
+        let mut o = Order::new("TST", Side::Buy, dec!(1.0), dec!(100))
+            .with_tif(TimeInForce::Ioc)
+            .with_flags(OrderFlags { reduce_only: true });
+        o.on_accepted();
+        o.on_fill(dec!(0.4)).unwrap();
+        o.on_expired();
+
+        assert_eq!(o.state, OrderState::Cancelled);
+        assert_eq!(o.remaining_signed_qty(), dec!(0));
+    }
+
+    #[test]
+    fn amend_rejected_restores_old_order() {
+        let mut o = Order::new("TST", Side::Sell, dec!(1.0), dec!(100));
+        o.on_accepted();
+        o.on_amend_requested(dec!(99), dec!(0.5)).unwrap();
+        assert_eq!(o.remaining_signed_qty(), dec!(-1.0));
+
+        o.on_amend_rejected();
+        assert_eq!(o.state, OrderState::Open { remaining: dec!(1.0) });
+        assert_eq!(o.limit_price, dec!(100));
+    }
+
+    #[test]
+    fn late_messages_are_errors_not_panics() {
+        let mut o = Order::new("TST", Side::Buy, dec!(1.0), dec!(100));
+        o.on_accepted();
+        o.on_fill(dec!(1.0)).unwrap();
+
+        assert!(matches!(
+            o.on_cancel_requested(),
+            Err(OrderError::InvalidTransition { event: "cancel request", .. })
+        ));
+        assert!(o.on_rejected(None).is_err());
+        assert!(o.on_cancel_confirmed().is_err());
+        assert_eq!(o.state, OrderState::Filled);
+
+        // redelivered fills are caught by trade id before this point, so a
+        // further one is more than was ordered
+        assert!(matches!(o.on_fill(dec!(0.1)), Err(OrderError::Overfill { .. })));
+        assert_eq!(o.state, OrderState::Filled);
+    }
+
+    #[test]
+    fn overfill_is_reported() {
+        let mut o = Order::new("TST", Side::Sell, dec!(1.0), dec!(100));
+        o.on_accepted();
+
+        assert!(matches!(o.on_fill(dec!(0)), Err(OrderError::NonPositiveFill { .. })));
+        assert!(matches!(o.on_fill(dec!(1.5)), Err(OrderError::Overfill { .. })));
+        assert_eq!(o.state, OrderState::Filled);
+    }
+
+    #[test]
+    fn cancel_rejected_restores_or_resolves() {
+        let mut o = Order::new("TST", Side::Buy, dec!(2.0), dec!(100));
+        o.on_accepted();
+        o.on_fill(dec!(0.5)).unwrap();
+        o.on_cancel_requested().unwrap();
+        assert_eq!(o.remaining_signed_qty(), dec!(1.5));
+
+        o.on_cancel_rejected(&CancelRejectReason::Transport("timeout".into())).unwrap();
+        assert_eq!(o.state, OrderState::PartiallyFilled { remaining: dec!(1.5) });
+
+        o.on_cancel_requested().unwrap();
+        o.on_cancel_rejected(&CancelRejectReason::AlreadyFilled).unwrap();
+        assert_eq!(o.state, OrderState::CancelPending { remaining: dec!(1.5) });
+        assert_eq!(o.remaining_signed_qty(), dec!(1.5));
+        o.on_fill(dec!(1.5)).unwrap();
+        assert_eq!(o.state, OrderState::Filled);
+
+        let mut o = Order::new("TST", Side::Sell, dec!(1.0), dec!(100));
+        o.on_accepted();
+        o.on_cancel_requested().unwrap();
+        o.on_cancel_rejected(&CancelRejectReason::UnknownOrder).unwrap();
+        assert_eq!(o.state, OrderState::Cancelled);
+        assert!(o.on_cancel_rejected(&CancelRejectReason::UnknownOrder).is_ok());
+    }
+
+    #[test]
+    fn unacked_order_counts_until_resolved() {
+        let mut o = Order::new("TST", Side::Sell, dec!(1.0), dec!(100));
+        o.on_sent().unwrap();
+        assert_eq!(o.remaining_signed_qty(), dec!(-1.0));
+
+        o.on_status_unknown();
+        assert_eq!(o.state, OrderState::Unknown);
+        assert_eq!(o.remaining_signed_qty(), dec!(-1.0));
+
+        // late fill and ack reconcile the order
+        o.on_fill(dec!(0.4)).unwrap();
+        assert_eq!(o.state, OrderState::PartiallyFilled { remaining: dec!(0.6) });
+        o.on_accepted();
+        assert_eq!(o.state, OrderState::PartiallyFilled { remaining: dec!(0.6) });
+
+        let mut o = Order::new("TST", Side::Buy, dec!(1.0), dec!(100));
+        o.on_sent().unwrap();
+        o.on_status_unknown();
+        o.on_rejected(None).unwrap();
+        assert_eq!(o.remaining_signed_qty(), dec!(0));
+    }
+
 }
@@ -0,0 +1,193 @@
+use tokio::sync::mpsc;
+
+use crate::broker::types::{BrokerCommand, LimitOrder};
+use crate::oms::order::OrderId;
+
+/// Broker commands issued during one turn of the OMS loop, sent together
+/// when the turn ends so places and cancels can share a venue request.
+#[derive(Debug)]
+pub struct Outbox {
+    pending: Vec<BrokerCommand>,
+    forward: mpsc::UnboundedSender<BrokerCommand>,
+}
+
+impl Outbox {
+    /// Starts the one task that feeds `broker_tx`. Every flush queues onto
+    /// it, so the broker sees turns in the order they ran even when its
+    /// queue is full.
+    pub fn new(broker_tx: mpsc::Sender<BrokerCommand>) -> Self {
+        let (forward, mut rx) = mpsc::unbounded_channel::<BrokerCommand>();
+        tokio::spawn(async move {
+            while let Some(cmd) = rx.recv().await {
+                if broker_tx.send(cmd).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Self { pending: Vec::new(), forward }
+    }
+
+    pub fn push(&mut self, cmd: BrokerCommand) {
+        self.pending.push(cmd);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Hands the turn's commands to the forwarder without waiting on the
+    /// broker, so a full broker queue never blocks the OMS loop
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        for cmd in coalesce(std::mem::take(&mut self.pending)) {
+            if self.forward.send(cmd).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Folds places and cancels into one batch each. Cancels go first so that
+/// quotes being replaced leave the book before their replacements land;
+/// a cancel of an order placed in the same turn has to follow its place.
+pub fn coalesce(cmds: Vec<BrokerCommand>) -> Vec<BrokerCommand> {
+    let mut places: Vec<LimitOrder> = Vec::new();
+    let mut cancels: Vec<(OrderId, String)> = Vec::new();
+    let mut others = Vec::new();
+
+    for cmd in cmds {
+        match cmd {
+            BrokerCommand::PlaceLimit { order_id, symbol, side, qty, price, tif, flags } => {
+                places.push(LimitOrder { order_id, symbol, side, qty, price, tif, flags });
+            }
+            BrokerCommand::PlaceBatch { orders } => places.extend(orders),
+            BrokerCommand::Cancel { order_id, symbol } => cancels.push((order_id, symbol)),
+            BrokerCommand::CancelBatch { orders } => cancels.extend(orders),
+            other => others.push(other),
+        }
+    }
+
+    let (late, early): (Vec<_>, Vec<_>) = cancels
+        .into_iter()
+        .partition(|(id, _)| places.iter().any(|o| o.order_id == *id));
+
+    let mut out = Vec::new();
+    out.extend(cancel_command(early));
+    out.extend(others);
+    out.extend(place_command(places));
+    out.extend(cancel_command(late));
+    out
+}
+
+fn cancel_command(mut orders: Vec<(OrderId, String)>) -> Option<BrokerCommand> {
+    match orders.len() {
+        0 => None,
+        1 => orders.pop().map(|(order_id, symbol)| BrokerCommand::Cancel { order_id, symbol }),
+        _ => Some(BrokerCommand::CancelBatch { orders }),
+    }
+}
+
+fn place_command(mut orders: Vec<LimitOrder>) -> Option<BrokerCommand> {
+    match orders.len() {
+        0 => None,
+        1 => orders.pop().map(|o| BrokerCommand::PlaceLimit {
+            order_id: o.order_id,
+            symbol: o.symbol,
+            side: o.side,
+            qty: o.qty,
+            price: o.price,
+            tif: o.tif,
+            flags: o.flags,
+        }),
+        _ => Some(BrokerCommand::PlaceBatch { orders }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oms::order::{OrderFlags, Side, TimeInForce};
+    use rust_decimal_macros::dec;
+
+    fn place(order_id: OrderId) -> BrokerCommand {
+        BrokerCommand::PlaceLimit {
+            order_id,
+            symbol: "TST".into(),
+            side: Side::Buy,
+            qty: dec!(1),
+            price: dec!(100),
+            tif: TimeInForce::Gtc,
+            flags: OrderFlags::default(),
+        }
+    }
+
+    fn cancel(order_id: OrderId) -> BrokerCommand {
+        BrokerCommand::Cancel { order_id, symbol: "TST".into() }
+    }
+
+    fn id() -> OrderId {
+        OrderId(uuid::Uuid::new_v4())
+    }
+
+    #[tokio::test]
+    async fn turns_reach_the_broker_in_flush_order() {
+        // a one-slot broker queue, so the first turn is still in flight
+        // when the second is flushed
+        let (broker_tx, mut broker_rx) = mpsc::channel(1);
+        let mut outbox = Outbox::new(broker_tx);
+        let (a, b, c) = (id(), id(), id());
+
+        outbox.push(place(a));
+        outbox.flush();
+        outbox.push(place(b));
+        outbox.flush();
+        outbox.push(cancel(a));
+        outbox.push(cancel(c));
+        outbox.flush();
+
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(broker_rx.recv().await.unwrap());
+        }
+        match seen.as_slice() {
+            [BrokerCommand::PlaceLimit { order_id: x, .. }, BrokerCommand::PlaceLimit { order_id: y, .. }, BrokerCommand::CancelBatch { orders }] => {
+                assert_eq!((*x, *y), (a, b));
+                assert_eq!(orders.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![a, c]);
+            }
+            other => panic!("unexpected commands {:?}", other),
+        }
+    }
+
+    #[test]
+    fn requote_becomes_one_cancel_batch_then_one_place_batch() {
+        let (old_bid, old_ask, bid, ask) = (id(), id(), id(), id());
+        let out = coalesce(vec![cancel(old_bid), cancel(old_ask), place(bid), place(ask)]);
+
+        match out.as_slice() {
+            [BrokerCommand::CancelBatch { orders: cancels }, BrokerCommand::PlaceBatch { orders: places }] => {
+                assert_eq!(cancels.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![old_bid, old_ask]);
+                assert_eq!(places.iter().map(|o| o.order_id).collect::<Vec<_>>(), vec![bid, ask]);
+            }
+            other => panic!("unexpected commands {:?}", other),
+        }
+    }
+
+    #[test]
+    fn single_commands_stay_single_and_same_turn_cancel_follows_its_place() {
+        let (old, new) = (id(), id());
+        let out = coalesce(vec![place(new), cancel(old), cancel(new)]);
+
+        match out.as_slice() {
+            [BrokerCommand::Cancel { order_id: a, .. }, BrokerCommand::PlaceLimit { order_id: b, .. }, BrokerCommand::Cancel { order_id: c, .. }] => {
+                assert_eq!((*a, *b, *c), (old, new, new));
+            }
+            other => panic!("unexpected commands {:?}", other),
+        }
+    }
+}
@@ -1,24 +1,155 @@
+use std::collections::VecDeque;
+
 use rust_decimal::Decimal;
 use rust_decimal::prelude::Signed;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use super::fill::{Fill, Liquidity};
+
+/// How closing fills are matched against what is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CostBasis {
+    /// One blended entry price
+    #[default]
+    Average,
+
+    /// Close the oldest open lot first
+    Fifo,
+
+    /// Close the newest open lot first
+    Lifo,
+}
+
+/// Quantity opened by one fill and not yet closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lot {
+    /// Signed, same direction as the position
+    pub qty: Decimal,
+    pub price: Decimal,
+    pub opened_ms: u64,
+}
+
+/// The part of a lot a closing fill took out
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClosedLot {
+    /// Signed as the lot was held
+    pub qty: Decimal,
+    pub open_price: Decimal,
+    pub close_price: Decimal,
+    pub opened_ms: u64,
+    pub closed_ms: u64,
+    /// Gross, before fees
+    pub realized_pnl: Decimal,
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub net_qty: Decimal,
     pub avg_price: Decimal,
+    /// Gross, before fees
     pub realized_pnl: Decimal,
+    /// Fees paid net of rebates
+    #[serde(default)]
+    pub fees: Decimal,
+    /// Notional traded as maker / taker
+    #[serde(default)]
+    pub maker_volume: Decimal,
+    #[serde(default)]
+    pub taker_volume: Decimal,
+
+    #[serde(default)]
+    pub cost_basis: CostBasis,
+    /// Open lots, oldest first. Empty under `CostBasis::Average`.
+    #[serde(default)]
+    pub lots: VecDeque<Lot>,
+    /// Lots closed by the latest fill, until the engine hands them to the
+    /// closed-lot ledger. Empty under `CostBasis::Average`.
+    #[serde(skip)]
+    pub closed_lots: Vec<ClosedLot>,
 }
 
 impl Position {
     pub fn new() -> Self {
+        Self::with_cost_basis(CostBasis::default())
+    }
+
+    pub fn with_cost_basis(cost_basis: CostBasis) -> Self {
         Self {
             net_qty: dec!(0),
             avg_price: dec!(0),
             realized_pnl: dec!(0),
+            fees: dec!(0),
+            maker_volume: dec!(0),
+            taker_volume: dec!(0),
+            cost_basis,
+            lots: VecDeque::new(),
+            closed_lots: Vec::new(),
+        }
+    }
+
+    /// Switches accounting mode. An open position moving to lots becomes a
+    /// single lot at its average price.
+    pub fn set_cost_basis(&mut self, cost_basis: CostBasis) {
+        if cost_basis == self.cost_basis {
+            return;
+        }
+        self.cost_basis = cost_basis;
+        self.reset_lots();
+    }
+
+    /// Overwrites size and entry, e.g. with venue truth
+    pub fn sync(&mut self, net_qty: Decimal, avg_price: Decimal) {
+        self.net_qty = net_qty;
+        self.avg_price = if net_qty == dec!(0) { dec!(0) } else { avg_price };
+        self.reset_lots();
+    }
+
+    fn reset_lots(&mut self) {
+        self.lots.clear();
+        if self.cost_basis != CostBasis::Average && self.net_qty != dec!(0) {
+            self.lots.push_back(Lot { qty: self.net_qty, price: self.avg_price, opened_ms: 0 });
+        }
+    }
+
+    pub fn net_pnl(&self) -> Decimal {
+        self.realized_pnl - self.fees
+    }
+
+    /// Open PnL of the position marked at `mark`
+    pub fn unrealized_pnl(&self, mark: Decimal) -> Decimal {
+        self.net_qty * (mark - self.avg_price)
+    }
+
+    /// Signed exposure at `mark`
+    pub fn notional(&self, mark: Decimal) -> Decimal {
+        self.net_qty * mark
+    }
+
+    /// Books an execution: economics plus fee and volume accounting.
+    /// `signed_qty` carries the order's direction.
+    pub fn on_fill(&mut self, signed_qty: Decimal, fill: &Fill) {
+        self.book(signed_qty, fill.price, fill.time_ms);
+        self.fees += fill.fee;
+        match fill.liquidity {
+            Liquidity::Maker => self.maker_volume += fill.notional(),
+            Liquidity::Taker => self.taker_volume += fill.notional(),
         }
     }
 
     pub fn apply_fill(&mut self, qty: Decimal, price: Decimal) {
+        self.book(qty, price, 0);
+    }
+
+    fn book(&mut self, qty: Decimal, price: Decimal, time_ms: u64) {
+        self.closed_lots.clear();
+        match self.cost_basis {
+            CostBasis::Average => self.book_average(qty, price),
+            CostBasis::Fifo | CostBasis::Lifo => self.book_lots(qty, price, time_ms),
+        }
+    }
+
+    fn book_average(&mut self, qty: Decimal, price: Decimal) {
         // same direction → adjust avg
         if self.net_qty == dec!(0) || self.net_qty.signum() == qty.signum() {
             let new_qty = self.net_qty + qty;
@@ -33,11 +164,146 @@ impl Position {
             let closing_qty = self.net_qty.abs().min(qty.abs());
             let pnl = closing_qty * (price - self.avg_price) * self.net_qty.signum();
             self.realized_pnl += pnl;
+            let was = self.net_qty;
             self.net_qty += qty;
 
             if self.net_qty == dec!(0) {
                 self.avg_price = dec!(0);
+            } else if self.net_qty.signum() != was.signum() {
+                // the remainder past flat is a fresh position
+                self.avg_price = price;
+            }
+        }
+    }
+
+    fn book_lots(&mut self, qty: Decimal, price: Decimal, time_ms: u64) {
+        let mut left = qty;
+
+        while left != dec!(0) {
+            let closing = match self.cost_basis {
+                CostBasis::Lifo => self.lots.back_mut(),
+                _ => self.lots.front_mut(),
+            };
+            let Some(lot) = closing.filter(|l| l.qty.signum() != left.signum()) else {
+                break;
+            };
+
+            let take = lot.qty.abs().min(left.abs()) * lot.qty.signum();
+            let pnl = take * (price - lot.price);
+            self.closed_lots.push(ClosedLot {
+                qty: take,
+                open_price: lot.price,
+                close_price: price,
+                opened_ms: lot.opened_ms,
+                closed_ms: time_ms,
+                realized_pnl: pnl,
+            });
+            self.realized_pnl += pnl;
+            lot.qty -= take;
+            left += take;
+
+            if lot.qty == dec!(0) {
+                match self.cost_basis {
+                    CostBasis::Lifo => self.lots.pop_back(),
+                    _ => self.lots.pop_front(),
+                };
             }
         }
+
+        if left != dec!(0) {
+            self.lots.push_back(Lot { qty: left, price, opened_ms: time_ms });
+        }
+
+        self.net_qty += qty;
+        let open: Decimal = self.lots.iter().map(|l| l.qty.abs()).sum();
+        self.avg_price = if open == dec!(0) {
+            dec!(0)
+        } else {
+            self.lots.iter().map(|l| l.qty.abs() * l.price).sum::<Decimal>() / open
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flip_reopens_at_fill_price() {
+        let mut pos = Position::new();
+        pos.apply_fill(dec!(2), dec!(100));
+        pos.apply_fill(dec!(-3), dec!(110));
+
+        assert_eq!(pos.net_qty, dec!(-1));
+        assert_eq!(pos.realized_pnl, dec!(20));
+        assert_eq!(pos.avg_price, dec!(110));
+
+        pos.apply_fill(dec!(1), dec!(105));
+        assert_eq!(pos.realized_pnl, dec!(25));
+        assert_eq!(pos.avg_price, dec!(0));
+    }
+
+    #[test]
+    fn fifo_and_lifo_close_different_lots() {
+        let mut fifo = Position::with_cost_basis(CostBasis::Fifo);
+        let mut lifo = Position::with_cost_basis(CostBasis::Lifo);
+        for pos in [&mut fifo, &mut lifo] {
+            pos.apply_fill(dec!(1), dec!(100));
+            pos.apply_fill(dec!(1), dec!(120));
+            assert_eq!(pos.avg_price, dec!(110));
+            pos.apply_fill(dec!(-1), dec!(130));
+        }
+
+        assert_eq!(fifo.realized_pnl, dec!(30));
+        assert_eq!(fifo.avg_price, dec!(120));
+        assert_eq!(lifo.realized_pnl, dec!(10));
+        assert_eq!(lifo.avg_price, dec!(100));
+
+        assert_eq!(fifo.closed_lots.len(), 1);
+        assert_eq!(fifo.closed_lots[0].open_price, dec!(100));
+        assert_eq!(fifo.closed_lots[0].close_price, dec!(130));
+    }
+
+    #[test]
+    fn lot_flip_closes_everything_then_opens() {
+        let mut pos = Position::with_cost_basis(CostBasis::Fifo);
+        pos.apply_fill(dec!(1), dec!(100));
+        pos.apply_fill(dec!(1), dec!(102));
+        pos.apply_fill(dec!(-3), dec!(104));
+
+        assert_eq!(pos.net_qty, dec!(-1));
+        assert_eq!(pos.realized_pnl, dec!(6));
+        assert_eq!(pos.closed_lots.len(), 2);
+        assert_eq!(pos.lots, VecDeque::from([Lot { qty: dec!(-1), price: dec!(104), opened_ms: 0 }]));
+        assert_eq!(pos.avg_price, dec!(104));
+    }
+
+    #[test]
+    fn closed_lots_hold_only_the_latest_fill() {
+        let mut pos = Position::with_cost_basis(CostBasis::Fifo);
+        pos.book(dec!(2), dec!(100), 1);
+        pos.book(dec!(-1), dec!(101), 2);
+        pos.book(dec!(-1), dec!(103), 3);
+
+        assert_eq!(pos.closed_lots.len(), 1);
+        assert_eq!(pos.closed_lots[0].closed_ms, 3);
+        assert_eq!(pos.closed_lots[0].realized_pnl, dec!(3));
+
+        pos.book(dec!(1), dec!(100), 4);
+        assert!(pos.closed_lots.is_empty());
+        assert_eq!(pos.realized_pnl, dec!(4));
+    }
+
+    #[test]
+    fn switching_to_lots_seeds_one_lot() {
+        let mut pos = Position::new();
+        pos.apply_fill(dec!(1), dec!(100));
+        pos.apply_fill(dec!(1), dec!(110));
+        pos.set_cost_basis(CostBasis::Fifo);
+
+        assert_eq!(pos.lots.len(), 1);
+        pos.apply_fill(dec!(-2), dec!(115));
+        assert_eq!(pos.realized_pnl, dec!(20));
+        assert!(pos.lots.is_empty());
     }
 }
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::engine::OmsEngine;
+use super::journal::JournalEntry;
+use super::order::{Order, OrderId, OrderState};
+use crate::broker::types::{VenueOrder, VenueState};
+
+/// What to do with resting orders the OMS did not place (or lost track of)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ForeignOrderPolicy {
+    /// Track them like our own; they count towards delta
+    Adopt,
+
+    /// Track them until the venue confirms they are cancelled
+    #[default]
+    Cancel,
+}
+
+/// Journal entries that bring the engine in line with the venue, plus the
+/// cancels to send for foreign orders.
+#[derive(Debug, Default)]
+pub struct ReconcilePlan {
+    pub entries: Vec<JournalEntry>,
+    pub cancels: Vec<(OrderId, String)>,
+}
+
+/// Compares the recovered engine with the venue. The venue wins:
+/// - our live orders it still has take its price and size; cancels we had
+///   in flight are sent again
+/// - our live orders it no longer has are Filled or Cancelled, by its fills
+/// - its orders we do not know are adopted, and cancelled if policy says so
+/// - positions are replaced by its positions
+pub fn plan(engine: &OmsEngine, venue: &VenueState, policy: ForeignOrderPolicy) -> ReconcilePlan {
+    let mut plan = ReconcilePlan::default();
+
+    let resting: HashMap<OrderId, &VenueOrder> = venue
+        .open_orders
+        .iter()
+        .map(|o| (o.order_id, o))
+        .collect();
+
+    let mut filled: HashMap<OrderId, Decimal> = HashMap::new();
+    for fill in &venue.fills {
+        if let Some(id) = fill.order_id {
+            *filled.entry(id).or_insert(dec!(0)) += fill.qty;
+        }
+    }
+
+    /* ---------- Our orders ---------- */
+
+    for order in engine.orders() {
+        if matches!(
+            order.state,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected
+        ) {
+            continue;
+        }
+
+        let mut synced = order.clone();
+
+        match resting.get(&order.id) {
+            Some(v) => {
+                synced.sync_resting(v.price, v.original_qty, v.remaining_qty);
+
+                // the cancel may never have reached the venue; we still want it gone
+                if let OrderState::CancelPending { .. } = order.state {
+                    synced.state = OrderState::CancelPending { remaining: v.remaining_qty };
+                    plan.cancels.push((order.id, order.symbol.clone()));
+                }
+            }
+            None => {
+                let venue_filled = filled.get(&order.id).copied().unwrap_or(dec!(0));
+                synced.state = if venue_filled >= order.original_qty {
+                    OrderState::Filled
+                } else {
+                    OrderState::Cancelled
+                };
+            }
+        }
+
+        if synced.state != order.state
+            || synced.limit_price != order.limit_price
+            || synced.original_qty != order.original_qty
+        {
+            plan.entries.push(JournalEntry::OrderSynced { order: synced });
+        }
+    }
+
+    /* ---------- Foreign orders ---------- */
+
+    for v in &venue.open_orders {
+        if engine.order(v.order_id).is_some() {
+            continue;
+        }
+
+        let mut order = Order::new(&v.symbol, v.side, v.original_qty, v.price)
+            .with_id(v.order_id)
+            .with_tif(v.tif)
+            .with_flags(v.flags);
+        order.sync_resting(v.price, v.original_qty, v.remaining_qty);
+
+        plan.entries.push(JournalEntry::OrderSynced { order });
+
+        if policy == ForeignOrderPolicy::Cancel {
+            plan.entries.push(JournalEntry::CancelRequested { order_id: v.order_id });
+            plan.cancels.push((v.order_id, v.symbol.clone()));
+        }
+    }
+
+    /* ---------- Positions ---------- */
+
+    let symbols: HashSet<String> = engine
+        .symbols()
+        .into_iter()
+        .chain(venue.positions.keys().cloned())
+        .collect();
+
+    for symbol in symbols {
+        let (net_qty, avg_price) = venue
+            .positions
+            .get(&symbol)
+            .map(|p| (p.net_qty, p.entry_price))
+            .unwrap_or((dec!(0), dec!(0)));
+
+        let ours = engine.position(&symbol);
+        let in_sync = match ours {
+            Some(p) => p.net_qty == net_qty && (net_qty == dec!(0) || p.avg_price == avg_price),
+            None => net_qty == dec!(0),
+        };
+
+        if !in_sync {
+            plan.entries.push(JournalEntry::PositionSynced { symbol, net_qty, avg_price });
+        }
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::types::{VenueFill, VenuePosition};
+    use crate::oms::order::{OrderFlags, Side, TimeInForce};
+
+    fn apply(engine: &mut OmsEngine, plan: &ReconcilePlan) {
+        for entry in &plan.entries {
+            engine.apply(entry).unwrap();
+        }
+    }
+
+    fn resting(order_id: OrderId, side: Side, original_qty: Decimal, remaining_qty: Decimal) -> VenueOrder {
+        VenueOrder {
+            order_id,
+            symbol: "TST".into(),
+            side,
+            price: dec!(100),
+            original_qty,
+            remaining_qty,
+            tif: TimeInForce::Gtc,
+            flags: OrderFlags::default(),
+        }
+    }
+
+    #[test]
+    fn own_orders_follow_the_venue() {
+        let mut engine = OmsEngine::new();
+        let live = engine.create_order("TST", Side::Buy, dec!(2), dec!(100));
+        let gone = engine.create_order("TST", Side::Sell, dec!(1), dec!(110));
+        let traded = engine.create_order("TST", Side::Sell, dec!(1), dec!(105));
+        for id in [live, gone, traded] {
+            engine.on_order_accepted(id).unwrap();
+        }
+
+        let venue = VenueState {
+            open_orders: vec![resting(live, Side::Buy, dec!(2), dec!(1.5))],
+            positions: HashMap::from([(
+                "TST".to_string(),
+                VenuePosition { net_qty: dec!(-0.5), entry_price: dec!(104) },
+            )]),
+            fills: vec![VenueFill {
+                order_id: Some(traded),
+                symbol: "TST".into(),
+                side: Side::Sell,
+                qty: dec!(1),
+                price: dec!(105),
+            }],
+        };
+
+        let plan = plan(&engine, &venue, ForeignOrderPolicy::Cancel);
+        assert!(plan.cancels.is_empty());
+        apply(&mut engine, &plan);
+
+        assert_eq!(engine.order(live).unwrap().state, OrderState::PartiallyFilled { remaining: dec!(1.5) });
+        assert_eq!(engine.order(gone).unwrap().state, OrderState::Cancelled);
+        assert_eq!(engine.order(traded).unwrap().state, OrderState::Filled);
+        assert_eq!(engine.position("TST").unwrap().net_qty, dec!(-0.5));
+
+        // target 0, position -0.5, resting bid 1.5
+        assert_eq!(engine.delta("TST"), dec!(-1.0));
+    }
+
+    #[test]
+    fn foreign_orders_are_adopted_or_cancelled() {
+        let foreign = OrderId(uuid::Uuid::new_v4());
+        let venue = VenueState {
+            open_orders: vec![resting(foreign, Side::Sell, dec!(1), dec!(1))],
+            ..Default::default()
+        };
+
+        let mut adopted = OmsEngine::new();
+        let p = plan(&adopted, &venue, ForeignOrderPolicy::Adopt);
+        apply(&mut adopted, &p);
+        assert!(p.cancels.is_empty());
+        assert_eq!(adopted.open_orders(None), vec![(foreign, "TST".to_string())]);
+        assert_eq!(adopted.delta("TST"), dec!(1));
+
+        let mut cancelled = OmsEngine::new();
+        let p = plan(&cancelled, &venue, ForeignOrderPolicy::Cancel);
+        apply(&mut cancelled, &p);
+        assert_eq!(p.cancels, vec![(foreign, "TST".to_string())]);
+        assert_eq!(
+            cancelled.order(foreign).unwrap().state,
+            OrderState::CancelPending { remaining: dec!(1) }
+        );
+
+        // nothing left to do the second time round
+        assert!(plan(&adopted, &venue, ForeignOrderPolicy::Adopt).entries.is_empty());
+    }
+
+    #[test]
+    fn pending_cancel_is_sent_again() {
+        let mut engine = OmsEngine::new();
+        let id = engine.create_order("TST", Side::Buy, dec!(2), dec!(100));
+        engine.on_order_accepted(id).unwrap();
+        engine.request_cancel(id).unwrap();
+
+        let venue = VenueState {
+            open_orders: vec![resting(id, Side::Buy, dec!(2), dec!(1.5))],
+            ..Default::default()
+        };
+
+        let p = plan(&engine, &venue, ForeignOrderPolicy::Cancel);
+        apply(&mut engine, &p);
+        assert_eq!(p.cancels, vec![(id, "TST".to_string())]);
+        assert_eq!(engine.order(id).unwrap().state, OrderState::CancelPending { remaining: dec!(1.5) });
+    }
+}
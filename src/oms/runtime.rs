@@ -1,36 +1,428 @@
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::Instant;
 
 use std::env;
 
 use ethers::signers::Wallet;
 use ethers::core::k256::ecdsa::SigningKey;
 
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use super::config::OmsConfig;
 use super::engine::OmsEngine;
-use super::event::OmsEvent;
+use super::error::{CommitError, OmsAnomaly, OmsError, OrderError};
+use super::event::{OmsEvent, FlattenLeg};
+use super::journal::{Journal, JournalEntry};
+use super::ledger::LotLedger;
+use super::position::ClosedLot;
+use super::reconcile;
+use super::drift::{DriftAction, PositionReconciler};
+use super::algo::{AlgoAction, AlgoEngine};
+use super::executor::{ExecAction, Executor};
+use super::handle::OmsHandle;
+use super::update::{updates_for, OmsUpdate};
+use super::outbox::Outbox;
+use crate::market::types::MarketEvent;
 use crate::broker::{Broker, sim::SimBroker, types::BrokerCommand};
 use crate::oms::snapshot::OmsSnapshot;
-use crate::oms::order::Side;
+use crate::oms::order::{CancelRejectReason, Order, OrderId, OrderState, RejectReason, Side};
 use crate::oms::state::TradingState;
-use hyperliquid_rust_sdk::BaseUrl;
+use crate::market::hyperliquid::load_instruments;
+use crate::market::instrument::InstrumentRegistry;
 
 use tracing::{info, warn, error};
 
 #[cfg(feature = "hyperliquid")]
 use crate::broker::HyperliquidBroker;
 
+/// How many refused events are kept around for inspection
+const QUARANTINE_CAPACITY: usize = 1024;
+
+/// Updates a slow subscriber may fall behind by before it starts missing them
+const UPDATE_CAPACITY: usize = 4096;
+
+/// Longest run of queued events before broker commands are flushed anyway
+const MAX_TURN_EVENTS: usize = 64;
+
+/// Status queries that find nothing before an unacked order counts as
+/// rejected; a late ack before then still goes through
+const MAX_STATUS_MISSES: u32 = 5;
+
+/// Times a refused cancel is sent again before the OMS gives up on it
+const MAX_CANCEL_RETRIES: u32 = 3;
+
 pub struct OmsRuntime {
     sender: mpsc::Sender<OmsEvent>,
+    anomaly_tx: broadcast::Sender<OmsAnomaly>,
+    update_tx: broadcast::Sender<OmsUpdate>,
+    instruments: Arc<InstrumentRegistry>,
+    broker: Arc<dyn Broker>,
 }
 
 impl OmsRuntime {
     pub fn sender(&self) -> mpsc::Sender<OmsEvent> {
         self.sender.clone()
     }
+
+    /// Trading rules the OMS and broker use, for strategies to quote with
+    pub fn instruments(&self) -> Arc<InstrumentRegistry> {
+        self.instruments.clone()
+    }
+
+    /// Typed client with the default timeout
+    pub fn handle(&self) -> OmsHandle {
+        OmsHandle::new(self.sender.clone(), self.update_tx.clone())
+    }
+
+    /// Order, fill, position, trading state and account changes as they happen
+    pub fn subscribe(&self) -> broadcast::Receiver<OmsUpdate> {
+        self.update_tx.subscribe()
+    }
+
+    /// Forwards books, trades and marks to the OMS for the executor, algos
+    /// and position marking, and to the broker
+    pub fn feed_market(&self, mut market_rx: broadcast::Receiver<MarketEvent>) {
+        let tx = self.sender.clone();
+        let broker = self.broker.clone();
+        tokio::spawn(async move {
+            loop {
+                match market_rx.recv().await {
+                    Ok(event) => {
+                        broker.on_market(&event);
+                        let event = match event {
+                            MarketEvent::Snapshot(snapshot) => OmsEvent::MarketData { snapshot },
+                            MarketEvent::Trade(trade) => OmsEvent::MarketTrade { trade },
+                            MarketEvent::Mark(mark) => OmsEvent::MarketMark { mark },
+                        };
+                        if tx.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Events the OMS refused to apply (late, duplicate or unknown)
+    pub fn subscribe_anomalies(&self) -> broadcast::Receiver<OmsAnomaly> {
+        self.anomaly_tx.subscribe()
+    }
+}
+
+/// Events that failed to apply, plus the channel that reports anomalies to RMS
+struct Quarantine {
+    events: VecDeque<OmsAnomaly>,
+    tx: broadcast::Sender<OmsAnomaly>,
+}
+
+impl Quarantine {
+    /// Keeps a refused entry and reports it
+    fn admit(&mut self, entry: &JournalEntry, error: OrderError) {
+        warn!("[OMS][QUARANTINE] {} ← {:?}", error, entry);
+
+        let anomaly = OmsAnomaly {
+            error,
+            event: format!("{:?}", entry),
+            applied: false,
+        };
+
+        if self.events.len() == QUARANTINE_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(anomaly.clone());
+
+        // nobody listening is fine
+        let _ = self.tx.send(anomaly);
+    }
+
+    /// Reports an entry that was applied with an anomaly; it is not kept,
+    /// since nothing about it was refused
+    fn report(&self, entry: &JournalEntry, error: OrderError) {
+        warn!("[OMS][ANOMALY] {} ← {:?}", error, entry);
+
+        let _ = self.tx.send(OmsAnomaly {
+            error,
+            event: format!("{:?}", entry),
+            applied: true,
+        });
+    }
+}
+
+/// Where each commit's outcome goes: refused entries to quarantine, applied
+/// ones to subscribers as `OmsUpdate`s
+struct Outcomes {
+    quarantine: Quarantine,
+    bus: broadcast::Sender<OmsUpdate>,
+    ledger: LotLedger,
+}
+
+impl Outcomes {
+    fn publish(&self, entry: &JournalEntry, oms: &OmsEngine) {
+        // nobody listening is fine
+        if self.bus.receiver_count() == 0 {
+            return;
+        }
+        for update in updates_for(entry, oms) {
+            let _ = self.bus.send(update);
+        }
+    }
+
+    /// Writes the lots closed by the entry at `seq` to the ledger and then
+    /// announces them. A ledger failure is logged; the fill stands.
+    fn record_lots(&mut self, seq: u64, lots: Vec<(String, ClosedLot)>) {
+        match self.ledger.append(seq, lots) {
+            Ok(records) => {
+                for record in records {
+                    let _ = self.bus.send(OmsUpdate::LotClosed { record });
+                }
+            }
+            Err(e) => error!("[OMS][LEDGER] failed to record lots closed at seq={}: {}", seq, e),
+        }
+    }
+
+    fn sync_ledger(&mut self) {
+        if let Err(e) = self.ledger.sync() {
+            error!("[OMS][LEDGER] sync failed: {}", e);
+        }
+    }
+}
+
+/// Journals an entry and then applies it, so what is on disk is always a
+/// superset of what is in memory. An entry the journal cannot take is not
+/// applied and trading halts; entries the engine refuses are quarantined.
+fn commit(
+    journal: &mut Journal,
+    outcomes: &mut Outcomes,
+    oms: &mut OmsEngine,
+    entry: JournalEntry,
+) -> Result<(), CommitError> {
+    if let Err(e) = journal.append(&entry) {
+        error!("[OMS][JOURNAL] failed to persist {:?}, not applied: {}", entry, e);
+        halt_unjournaled(outcomes, oms, &format!("journal write failed: {}", e));
+        return Err(CommitError::Journal(e.kind()));
+    }
+
+    let applied = oms.apply(&entry);
+    match &applied {
+        Err(e) if e.was_applied() => {
+            outcomes.publish(&entry, oms);
+            outcomes.quarantine.report(&entry, e.clone());
+        }
+        Err(e) => outcomes.quarantine.admit(&entry, e.clone()),
+        Ok(()) => outcomes.publish(&entry, oms),
+    }
+
+    let lots = oms.take_closed_lots();
+    if !lots.is_empty() {
+        outcomes.record_lots(journal.last_seq(), lots);
+    }
+
+    if journal.should_compact() {
+        // compaction drops the records the ledger would catch up from
+        outcomes.sync_ledger();
+        if let Err(e) = journal.compact(oms) {
+            error!("[OMS][JOURNAL] compaction failed: {}", e);
+        }
+    }
+
+    applied.map_err(CommitError::Refused)
+}
+
+/// Halts trading once the journal stops taking writes. The halt itself can
+/// not be journaled, so only memory and subscribers learn of it.
+fn halt_unjournaled(outcomes: &Outcomes, oms: &mut OmsEngine, reason: &str) {
+    let from = oms.get_trading_state();
+    if from == TradingState::Halted {
+        return;
+    }
+
+    error!("[OMS][STATE] {:?} → {:?}: {}", from, TradingState::Halted, reason);
+    oms.set_trading_state(TradingState::Halted);
+    if outcomes.bus.receiver_count() > 0 {
+        let _ = outcomes.bus.send(OmsUpdate::TradingStateChanged {
+            state: TradingState::Halted,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Ends an OMS turn: makes its journal entries durable, then hands what it
+/// asked of the broker over. Commands still go out if the sync fails, since
+/// most of what a halted book sends are cancels.
+fn end_turn(journal: &mut Journal, outcomes: &mut Outcomes, oms: &mut OmsEngine, outbox: &mut Outbox) {
+    outcomes.sync_ledger();
+    if let Err(e) = journal.sync() {
+        error!("[OMS][JOURNAL] sync failed: {}", e);
+        halt_unjournaled(outcomes, oms, &format!("journal sync failed: {}", e));
+    }
+    outbox.flush();
 }
 
-pub async fn start_oms() -> OmsRuntime {
+/// Moves to `to` if the state machine allows it, logging why. `rearm` marks
+/// an operator action, the only way out of `Halted`.
+fn set_trading_state(
+    journal: &mut Journal,
+    outcomes: &mut Outcomes,
+    oms: &mut OmsEngine,
+    to: TradingState,
+    reason: &str,
+    rearm: bool,
+) -> bool {
+    let from = oms.get_trading_state();
+    if !from.allows(to, rearm) {
+        warn!("[OMS][STATE] refusing {:?} → {:?}: {}", from, to, reason);
+        return false;
+    }
+
+    warn!("[OMS][STATE] {:?} → {:?}: {}", from, to, reason);
+    commit(journal, outcomes, oms, JournalEntry::TradingState { state: to, reason: reason.to_string() }).is_ok()
+}
+
+/// Creates an order and hands it to the broker. Shared by strategy
+/// `CreateOrder`s and the executor. An order that breaks an instrument rule
+/// is recorded and rejected without reaching the broker, and nothing the
+/// journal did not take is sent.
+fn place_order(
+    journal: &mut Journal,
+    outcomes: &mut Outcomes,
+    oms: &mut OmsEngine,
+    ack_clock: &mut HashMap<OrderId, Instant>,
+    outbox: &mut Outbox,
+    mut order: Order,
+) -> Result<(), OmsError> {
+    // what is journaled is what the venue gets: the quantized price and size
+    let verdict = oms.validate(&mut order);
+
+    let cmd = BrokerCommand::PlaceLimit {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side,
+        qty: order.original_qty,
+        price: order.limit_price,
+        tif: order.tif,
+        flags: order.flags,
+    };
+    info!(
+        "[OMS] order created {:?} {} {:?} qty={} price={} tif={:?} {:?}",
+        order.id, order.symbol, order.side, order.original_qty, order.limit_price, order.tif, order.flags
+    );
+
+    let oid = order.id;
+    commit(journal, outcomes, oms, JournalEntry::OrderCreated { order })
+        .map_err(|e| OmsError::Rejected(e.to_string()))?;
+
+    if let Err(reason) = verdict {
+        warn!("[OMS] order {:?} rejected locally: {}", oid, reason);
+        let _ = commit(
+            journal,
+            outcomes,
+            oms,
+            JournalEntry::OrderRejected { order_id: oid, reason: Some(reason.clone()) },
+        );
+        return Err(OmsError::Invalid(reason));
+    }
+
+    commit(journal, outcomes, oms, JournalEntry::OrderSent { order_id: oid })
+        .map_err(|e| OmsError::Rejected(e.to_string()))?;
+    ack_clock.insert(oid, Instant::now());
+    outbox.push(cmd);
+    Ok(())
+}
+
+/// Moves a live order in place. Orders with anything in flight are left alone.
+fn amend_order(
+    journal: &mut Journal,
+    outcomes: &mut Outcomes,
+    oms: &mut OmsEngine,
+    outbox: &mut Outbox,
+    order_id: OrderId,
+    price: Decimal,
+    qty: Decimal,
+) {
+    let (symbol, side, tif, flags) = match oms.order(order_id) {
+        Some(o) if matches!(
+            o.state,
+            OrderState::Open { .. } | OrderState::PartiallyFilled { .. }
+        ) => (o.symbol.clone(), o.side, o.tif, o.flags),
+        Some(o) => {
+            warn!("[OMS] cannot amend {:?} in state {:?}", order_id, o.state);
+            return;
+        }
+        None => {
+            warn!("[OMS] cannot amend unknown order {:?}", order_id);
+            return;
+        }
+    };
+    let (price, qty) = match oms.quantize(&symbol, side, price, qty) {
+        Ok(on_grid) => on_grid,
+        Err(reason) => {
+            warn!("[OMS] amend {:?} refused locally: {}", order_id, reason);
+            return;
+        }
+    };
+
+    if commit(journal, outcomes, oms, JournalEntry::AmendRequested { order_id, price, qty }).is_err() {
+        return;
+    }
+    info!(
+        "[OMS] amend requested {:?} {} → qty={} price={}",
+        order_id, symbol, qty, price
+    );
+
+    outbox.push(BrokerCommand::Modify {
+        order_id,
+        symbol,
+        side,
+        qty,
+        price,
+        tif,
+        flags,
+    });
+}
+
+/// Marks an order CancelPending and asks the broker to cancel it
+fn cancel_order(
+    journal: &mut Journal,
+    outcomes: &mut Outcomes,
+    oms: &mut OmsEngine,
+    outbox: &mut Outbox,
+    order_id: OrderId,
+) {
+    if commit(journal, outcomes, oms, JournalEntry::CancelRequested { order_id }).is_err() {
+        return;
+    }
+    let Some(symbol) = oms.order(order_id).map(|o| o.symbol.clone()) else {
+        return;
+    };
+
+    outbox.push(BrokerCommand::Cancel { order_id, symbol });
+}
+
+/// Matches opposing strategy targets on `symbol` at the current mark, so
+/// that part of them never goes to the venue
+fn cross_internal(
+    journal: &mut Journal,
+    outcomes: &mut Outcomes,
+    oms: &mut OmsEngine,
+    symbol: &str,
+) {
+    let Some(price) = oms.mark(symbol) else {
+        return;
+    };
+    let qty = oms.crossable(symbol);
+    if qty == dec!(0) {
+        return;
+    }
+
+    let _ = commit(journal, outcomes, oms, JournalEntry::InternalCross { symbol: symbol.to_string(), price });
+    info!("[OMS] {} crossed {} internally @ {}", symbol, qty, price);
+}
+
+pub async fn start_oms(config: OmsConfig) -> OmsRuntime {
     let (tx, mut rx) = mpsc::channel::<OmsEvent>(1024);
     let (broker_tx, broker_rx) = mpsc::channel::<BrokerCommand>(1024);
     // ---- WALLET (TESTNET) ----
@@ -41,12 +433,20 @@ pub async fn start_oms() -> OmsRuntime {
         .parse()
         .expect("invalid testnet private key");
 
+    let instruments = Arc::new(if cfg!(feature = "hyperliquid") {
+        load_instruments(config.network)
+            .await
+            .expect("failed to load instruments")
+    } else {
+        SimBroker::instruments()
+    });
+
     let broker: Arc<dyn Broker> = if cfg!(feature = "hyperliquid") {
         Arc::new(
             HyperliquidBroker::new(
                 wallet,
-                // BaseUrl::Testnet,
-                BaseUrl::Mainnet,
+                config.network,
+                instruments.clone(),
                 broker_tx.clone(),
                 Mutex::new(Some(broker_rx)),
                 tx.clone(),
@@ -63,208 +463,670 @@ pub async fn start_oms() -> OmsRuntime {
             )
         )
     };
-    
+
+    let (mut journal, mut oms) = Journal::recover(&config.journal_dir, config.compact_every, config.cost_basis)
+        .expect("failed to recover OMS journal");
+    oms.set_instruments(instruments.clone());
+
+    let (anomaly_tx, _) = broadcast::channel::<OmsAnomaly>(256);
+    let (update_tx, _) = broadcast::channel::<OmsUpdate>(UPDATE_CAPACITY);
+    let mut outcomes = Outcomes {
+        quarantine: Quarantine {
+            events: VecDeque::with_capacity(QUARANTINE_CAPACITY),
+            tx: anomaly_tx.clone(),
+        },
+        bus: update_tx.clone(),
+        ledger: LotLedger::open(&config.journal_dir).expect("failed to open closed-lot ledger"),
+    };
+    // lots closed by records the ledger missed before the last shutdown
+    for (seq, lots) in journal.take_replayed_lots() {
+        outcomes.record_lots(seq, lots);
+    }
+
+    // ---- RECONCILE WITH VENUE ----
+    // runs before the broker starts and before start_oms returns, so no
+    // strategy can trade on a stale book
+    let venue = broker
+        .venue_state()
+        .await
+        .expect("startup reconciliation failed");
+
+    let plan = reconcile::plan(&oms, &venue, config.foreign_orders);
+    info!(
+        "[OMS] reconciling: {} venue orders, {} venue positions → {} changes, {} cancels",
+        venue.open_orders.len(),
+        venue.positions.len(),
+        plan.entries.len(),
+        plan.cancels.len()
+    );
+
+    for entry in plan.entries {
+        let _ = commit(&mut journal, &mut outcomes, &mut oms, entry);
+    }
+    let mut outbox = Outbox::new(broker_tx);
+    if !plan.cancels.is_empty() {
+        outbox.push(BrokerCommand::CancelBatch { orders: plan.cancels });
+    }
+    end_turn(&mut journal, &mut outcomes, &mut oms, &mut outbox);
+
     broker.clone().start();
-    drop(broker);
+
+    // drives ack timeouts
+    {
+        let tx = tx.clone();
+        let tick_interval = config.tick_interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(tick_interval);
+            loop {
+                ticker.tick().await;
+                if tx.send(OmsEvent::Tick).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    // when each unacked order was last sent or queried; not journaled, so
+    // after a restart every unacked order is queried on the first tick
+    let mut ack_clock: HashMap<OrderId, Instant> = HashMap::new();
+
+    // status queries per unacked order that found nothing at the venue
+    let mut status_misses: HashMap<OrderId, u32> = HashMap::new();
+
+    // orders whose cancel was refused: when, and how many times so far.
+    // Sent again on a later tick; not journaled either
+    let mut cancel_retry: HashMap<OrderId, (Instant, u32)> = HashMap::new();
+
+    let mut drift_check = PositionReconciler::new(config.drift.clone());
+
+    let mut executor = Executor::new();
+    // symbols the executor should look at once the current event is applied
+    let mut exec_due: Vec<String> = Vec::new();
+
+    let mut algos = AlgoEngine::new();
+    let mut algo_due = false;
+    let mut algo_actions: Vec<AlgoAction> = Vec::new();
     
     tokio::spawn(async move {
-        let mut oms = OmsEngine::new();
+        info!(
+            "[OMS] started with {} orders across {:?}",
+            oms.order_views().len(),
+            oms.symbols()
+        );
 
-        info!("[OMS] started");
+        // events handled since the outbox was last flushed
+        let mut turn = 0;
+
+        loop {
+            // a turn lasts while events are already queued; what it asked
+            // of the broker goes out as one batch when it ends
+            if turn >= MAX_TURN_EVENTS {
+                end_turn(&mut journal, &mut outcomes, &mut oms, &mut outbox);
+                turn = 0;
+            }
+            let event = match rx.try_recv() {
+                Ok(event) => event,
+                Err(mpsc::error::TryRecvError::Empty) => {
+                    end_turn(&mut journal, &mut outcomes, &mut oms, &mut outbox);
+                    turn = 0;
+                    match rx.recv().await {
+                        Some(event) => event,
+                        None => break,
+                    }
+                }
+                Err(mpsc::error::TryRecvError::Disconnected) => break,
+            };
+            turn += 1;
 
-        while let Some(event) = rx.recv().await {
-            info!("[OMS] event received: {:?}", event);
+            if !matches!(
+                event,
+                OmsEvent::Tick
+                    | OmsEvent::MarketData { .. }
+                    | OmsEvent::MarketTrade { .. }
+                    | OmsEvent::MarketMark { .. }
+            ) {
+                info!("[OMS] event received: {:?}", event);
+            }
 
             match event {
-                OmsEvent::SetTarget { qty } => {
-                    oms.set_target_position(qty);
-                    info!("[OMS] target set → delta = {}", oms.delta());
+                OmsEvent::SetTarget { symbol, qty, exec, strategy } => {
+                    let _ = commit(
+                        &mut journal,
+                        &mut outcomes,
+                        &mut oms,
+                        JournalEntry::SetTarget { symbol: symbol.clone(), qty, strategy: strategy.clone() },
+                    );
+                    cross_internal(&mut journal, &mut outcomes, &mut oms, &symbol);
+                    info!(
+                        "[OMS] {} target set for {:?} → delta = {}",
+                        symbol,
+                        strategy,
+                        oms.delta(&symbol)
+                    );
+
+                    executor.set_params(&symbol, strategy.as_deref(), exec);
+                    if executor.is_executing(&symbol) {
+                        exec_due.push(symbol);
+                    }
                 }
 
-                OmsEvent::CreateOrder { side, qty, price } => {
-                    if oms.get_trading_state() != TradingState::Running {
+                OmsEvent::MarketData { snapshot } => {
+                    if let Some(price) = config.mark_source.from_book(&snapshot) {
+                        oms.set_mark(&snapshot.symbol, price);
+                    }
+                    // wants that could not cross before the first mark
+                    cross_internal(&mut journal, &mut outcomes, &mut oms, &snapshot.symbol);
+                    executor.on_book(&snapshot);
+                    if executor.is_executing(&snapshot.symbol) {
+                        exec_due.push(snapshot.symbol);
+                    }
+                }
+
+                OmsEvent::CreateOrder { order_id, symbol, side, qty, price, tif, mut flags, tag, reply } => {
+                    if oms.order(order_id).is_some() {
+                        warn!("[OMS] rejecting CreateOrder {:?} — id already taken", order_id);
+                        if let Some(reply) = reply {
+                            let _ = reply.send(Err(OmsError::Rejected("order id already taken".into())));
+                        }
+                        continue;
+                    }
+
+                    let state = oms.get_trading_state();
+                    let allowed = match state {
+                        TradingState::Running => true,
+                        TradingState::ReduceOnly => oms.reduces(&symbol, side, qty),
+                        _ => false,
+                    };
+                    if !allowed {
                         warn!(
-                            "[OMS] rejecting CreateOrder {:?} {:?} — trading halted",
-                            side, qty
+                            "[OMS] rejecting CreateOrder {} {:?} {:?} — trading {:?}",
+                            symbol, side, qty, state
                         );
+                        if let Some(reply) = reply {
+                            let _ = reply.send(Err(OmsError::Rejected(format!("trading {:?}", state))));
+                        }
                         continue;
                     }
+                    // the venue enforces it too, in case a fill lands first
+                    if state == TradingState::ReduceOnly {
+                        flags.reduce_only = true;
+                    }
 
-                    let oid = oms.create_order(side, qty, price);
-                    info!(
-                        "[OMS] order created {:?} {:?} qty={} price={}",
-                        oid, side, qty, price
-                    );
-                    let broker_tx = broker_tx.clone();
-                    tokio::spawn(async move {
-                        broker_tx
-                            .send(BrokerCommand::PlaceLimit {
-                                order_id: oid,
-                                side,
-                                qty,
-                                price,
-                            })
-                        .await
-                            .unwrap();
-                        });
+                    let order = Order::new(&symbol, side, qty, price)
+                        .with_id(order_id)
+                        .with_tif(tif)
+                        .with_flags(flags)
+                        .with_tag(tag);
+                    let placed = place_order(&mut journal, &mut outcomes, &mut oms, &mut ack_clock, &mut outbox, order);
+                    if let Some(reply) = reply {
+                        let _ = reply.send(placed.map(|()| order_id));
+                    }
+                }
+
+                OmsEvent::AmendOrder { order_id, price, qty } => {
+                    let state = oms.get_trading_state();
+                    let allowed = match state {
+                        TradingState::Running => true,
+                        TradingState::ReduceOnly => oms
+                            .order(order_id)
+                            .is_some_and(|o| oms.reduces(&o.symbol, o.side, qty)),
+                        _ => false,
+                    };
+                    if !allowed {
+                        warn!("[OMS] rejecting AmendOrder {:?} — trading {:?}", order_id, state);
+                        continue;
+                    }
+
+                    amend_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id, price, qty);
+                }
+
+                OmsEvent::MarketMark { mark } => {
+                    if let Some(price) = config.mark_source.from_mark(&mark) {
+                        oms.set_mark(&mark.symbol, price);
+                    }
+                }
+
+                OmsEvent::MarketTrade { trade } => {
+                    if let Some(price) = config.mark_source.from_trade(&trade) {
+                        oms.set_mark(&trade.symbol, price);
+                    }
+                    algos.on_trade(&trade);
+                    algo_due = true;
+                }
+
+                OmsEvent::StartAlgo { symbol, side, qty, limit_price, kind, reply } => {
+                    let state = oms.get_trading_state();
+                    if state != TradingState::Running {
+                        warn!("[OMS] rejecting {:?} algo on {} — trading {:?}", kind, symbol, state);
+                        let _ = reply.send(Err(OmsError::Rejected(format!("trading {:?}", state))));
+                        continue;
+                    }
+
+                    let id = algos.start(&symbol, side, qty, limit_price, kind, Instant::now());
+                    info!("[OMS][ALGO] started {:?} {} {:?} qty={} limit={}", id, symbol, side, qty, limit_price);
+                    let _ = reply.send(Ok(id));
+                    algo_due = true;
+                }
+
+                OmsEvent::PauseAlgo { id } => {
+                    info!("[OMS][ALGO] pause {:?}", id);
+                    algo_actions.extend(algos.pause(id, &oms, Instant::now()));
+                }
+
+                OmsEvent::ResumeAlgo { id } => {
+                    info!("[OMS][ALGO] resume {:?}", id);
+                    algos.resume(id, Instant::now());
+                    algo_due = true;
+                }
+
+                OmsEvent::CancelAlgo { id } => {
+                    info!("[OMS][ALGO] cancel {:?}", id);
+                    algo_actions.extend(algos.cancel(id, &oms));
+                }
+
+                OmsEvent::GetAlgos { reply } => {
+                    let _ = reply.send(algos.views());
+                }
+
+                OmsEvent::AmendAccepted { order_id } => {
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::AmendAccepted { order_id });
+                    info!("[OMS] amend accepted {:?}", order_id);
+                }
+
+                OmsEvent::AmendRejected { order_id } => {
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::AmendRejected { order_id });
+                    info!("[OMS] amend rejected {:?}", order_id);
                 }
 
                 OmsEvent::OrderAccepted { order_id } => {
-                    oms.on_order_accepted(order_id);
-                    info!(
-                        "[OMS] order accepted {:?}, delta={}",
-                        order_id,
-                        oms.delta()
-                    );
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::OrderAccepted { order_id });
+                    info!("[OMS] order accepted {:?}", order_id);
                 }
 
-                OmsEvent::OrderRejected{ order_id } => {
-                    oms.on_order_rejected(order_id);
-                    info!(
-                        "[OMS] order rejected {:?}, delta={}",
-                        order_id,
-                        oms.delta()
-                    );
+                OmsEvent::OrderRejected{ order_id, reason } => {
+                    info!("[OMS] order rejected {:?} → {}", order_id, reason);
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::OrderRejected { order_id, reason: Some(reason) });
                 }
 
-                OmsEvent::Fill {
-                    order_id,
-                    qty,
-                    price,
-                } => {
-                    oms.on_fill(order_id, qty, price);
-                    let pos = oms.position();
-                    info!(
-                        "[OMS] fill {:?} qty={} price={} → net={} avg={} pnl={}",
-                        order_id,
-                        qty,
-                        price,
-                        pos.net_qty,
-                        pos.avg_price,
-                        pos.realized_pnl
+                OmsEvent::OrderStatusUnknown { order_id } => {
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::StatusUnknown { order_id });
+                    warn!("[OMS] order {:?} status unknown, querying venue", order_id);
+
+                    if let Some(symbol) = oms.order(order_id).map(|o| o.symbol.clone()) {
+                        ack_clock.insert(order_id, Instant::now());
+                        outbox.push(BrokerCommand::QueryStatus { order_id, symbol });
+                    }
+                }
+
+                OmsEvent::OrderNotFound { order_id } => {
+                    // answered by an ack or fill in the meantime
+                    if !oms.order(order_id).is_some_and(|o| o.is_unacked()) {
+                        continue;
+                    }
+                    let misses = status_misses.entry(order_id).or_insert(0);
+                    *misses += 1;
+
+                    if *misses < MAX_STATUS_MISSES {
+                        // the tick asks again once the ack timeout passes
+                        info!("[OMS] {:?} not found at venue ({}/{})", order_id, misses, MAX_STATUS_MISSES);
+                        continue;
+                    }
+
+                    warn!("[OMS] {:?} not found after {} queries, treating as rejected", order_id, misses);
+                    status_misses.remove(&order_id);
+                    let _ = commit(
+                        &mut journal,
+                        &mut outcomes,
+                        &mut oms,
+                        JournalEntry::OrderRejected {
+                            order_id,
+                            reason: Some(RejectReason::Unknown("not found at venue".into())),
+                        },
                     );
                 }
 
+                OmsEvent::Fill { fill } => {
+                    let (order_id, qty, price) = (fill.order_id, fill.qty, fill.price);
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::Fill { fill: fill.clone() });
+                    if let Some(id) = algos.on_fill(order_id, qty, price) {
+                        if let Some(parent) = algos.parent(id) {
+                            info!(
+                                "[OMS][ALGO] {:?} filled {}/{} avg={} {:?}",
+                                id, parent.filled, parent.qty, parent.avg_price(), parent.state
+                            );
+                        }
+                    }
+                    let symbol = oms.order(order_id).map(|o| o.symbol.clone()).unwrap_or_default();
+                    if let Some(pos) = oms.position(&symbol) {
+                        info!(
+                            "[OMS] fill {:?} {} qty={} price={} fee={} {:?} → net={} avg={} pnl={} fees={} net_pnl={}",
+                            order_id,
+                            symbol,
+                            qty,
+                            price,
+                            fill.fee,
+                            fill.liquidity,
+                            pos.net_qty,
+                            pos.avg_price,
+                            pos.realized_pnl,
+                            pos.fees,
+                            pos.net_pnl()
+                        );
+                    }
+                }
+
                 OmsEvent::CancelConfirmed { order_id } => {
-                    oms.on_cancel_confirmed(order_id);
-                    info!(
-                        "[OMS] cancel confirmed {:?}, delta={}",
-                        order_id,
-                        oms.delta()
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::CancelConfirmed { order_id });
+                    info!("[OMS] cancel confirmed {:?}", order_id);
+                }
+
+                OmsEvent::CancelRejected { order_id, reason } => {
+                    warn!("[OMS] cancel rejected {:?}: {:?}", order_id, reason);
+                    let retry = matches!(reason, CancelRejectReason::Refused(_) | CancelRejectReason::Transport(_));
+                    let applied = commit(
+                        &mut journal,
+                        &mut outcomes,
+                        &mut oms,
+                        JournalEntry::CancelRejected { order_id, reason },
                     );
+
+                    // the order is live again but we still want it gone
+                    if retry && applied.is_ok() {
+                        let attempts = cancel_retry.get(&order_id).map_or(0, |(_, n)| *n) + 1;
+                        if attempts > MAX_CANCEL_RETRIES {
+                            cancel_retry.remove(&order_id);
+                            error!("[OMS] cancel {:?} refused {} times, leaving the order live", order_id, attempts);
+                        } else {
+                            cancel_retry.insert(order_id, (Instant::now(), attempts));
+                        }
+                    }
+                }
+
+                OmsEvent::OrderExpired { order_id } => {
+                    let _ = commit(&mut journal, &mut outcomes, &mut oms, JournalEntry::OrderExpired { order_id });
+                    info!("[OMS] order expired {:?}", order_id);
                 }
 
-                OmsEvent::GetDelta { reply } => {
-                    let _ = reply.send(oms.delta());
+                OmsEvent::GetDelta { symbol, reply } => {
+                    let _ = reply.send(oms.delta(&symbol));
                 }
 
-                OmsEvent::CancelAll => {
+                OmsEvent::CancelAll { symbol, tag } => {
                     // if oms.get_trading_state() == TradingState::Halted {
                     //     continue;
                     // }
-                    for order_id in oms.open_order_ids() {
-                        oms.request_cancel(order_id);
-
-                        let broker_tx = broker_tx.clone();
-                        tokio::spawn(async move {
-                            broker_tx
-                                .send(BrokerCommand::Cancel { order_id })
-                                .await
-                                .unwrap();
-                            });
+                    for (order_id, _) in oms.open_orders_tagged(symbol.as_deref(), tag.as_deref()) {
+                        cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
                     }
                 }
 
+                OmsEvent::GetOrder { order_id, reply } => {
+                    let _ = reply.send(oms.order(order_id).map(|o| o.view()));
+                }
+
+                OmsEvent::GetClosedLots { symbol, reply } => {
+                    let lots = outcomes
+                        .ledger
+                        .read(symbol.as_deref())
+                        .map_err(|e| OmsError::Rejected(format!("closed-lot ledger unreadable: {}", e)));
+                    let _ = reply.send(lots);
+                }
+
                 OmsEvent::GetSnapshot { reply } => {
-                    let pos = oms.position();
                     let snapshot = OmsSnapshot {
                         orders: oms.order_views(),
-                        net_position: pos.net_qty,
-                        avg_price: pos.avg_price,
+                        positions: oms.position_views(),
+                        tags: oms.tag_views(),
                     };
                     let _ = reply.send(snapshot);
                 }
 
                 OmsEvent::UpdateAccountSnapshot { snapshot } => {
+                    for drift in drift_check.check(&oms, &snapshot) {
+                        warn!(
+                            "[OMS][DRIFT] {} ours={} venue={} persisted {} snapshots",
+                            drift.symbol, drift.ours, drift.venue, config.drift.persist_for
+                        );
+
+                        match drift_check.action() {
+                            DriftAction::Correct => {
+                                // close at our cost so no pnl is invented,
+                                // open at the venue's entry
+                                let price = match oms.position(&drift.symbol) {
+                                    Some(p) if p.net_qty != dec!(0) => p.avg_price,
+                                    _ => snapshot.entry_price(&drift.symbol).unwrap_or(dec!(0)),
+                                };
+                                let _ = commit(
+                                    &mut journal,
+                                    &mut outcomes,
+                                    &mut oms,
+                                    JournalEntry::AdjustmentFill {
+                                        symbol: drift.symbol.clone(),
+                                        qty: drift.correction(),
+                                        price,
+                                    },
+                                );
+                                warn!("[OMS][DRIFT] {} adjusted by {}", drift.symbol, drift.correction());
+                            }
+                            DriftAction::Halt => {
+                                let reason = format!("{} position drift", drift.symbol);
+                                if set_trading_state(
+                                    &mut journal,
+                                    &mut outcomes,
+                                    &mut oms,
+                                    TradingState::Halted,
+                                    &reason,
+                                    false,
+                                ) {
+                                    error!("[OMS][DRIFT] TRADING HALTED on {}", reason);
+                                }
+                            }
+                        }
+                    }
+
+                    if outcomes.bus.receiver_count() > 0 {
+                        let _ = outcomes.bus.send(OmsUpdate::AccountUpdated { account: snapshot.clone() });
+                    }
                     oms.update_account_snapshot(Some(snapshot));
                 }
 
+                OmsEvent::GetQuarantine { reply } => {
+                    let _ = reply.send(outcomes.quarantine.events.iter().cloned().collect());
+                }
+
                 OmsEvent::GetAccountSnapshot { reply } => {
-                    if let Some(ref acc) = oms.get_account_snapshot() {
-                        let _ = reply.send(acc.clone());
-                    }
+                    let _ = reply.send(oms.get_account_snapshot());
                 }
 
-                OmsEvent::Flatten { qty, limit_px } => {
+                OmsEvent::Flatten { legs } => {
                     info!("[OMS] FLATTEN requested");
 
                     // 1. cancel all live orders
-                    for order_id in oms.open_order_ids() {
-                        oms.request_cancel(order_id);
-
-                        let broker_tx = broker_tx.clone();
-                        tokio::spawn(async move {
-                            let _ = broker_tx
-                                .send(BrokerCommand::Cancel { order_id })
-                                .await;
-                            });
+                    for (order_id, _) in oms.open_orders(None) {
+                        cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
                     }
 
-                    if qty != dec!(0) {
+                    for FlattenLeg { symbol, qty, limit_px } in legs {
+                        if qty == dec!(0) {
+                            continue;
+                        }
+
                         let side = if qty > dec!(0) { Side::Sell } else { Side::Buy };
                         // let qty = net.abs();
 
-                        info!("[OMS] flattening net={} via {:?}", qty, side);
+                        info!("[OMS] flattening {} net={} via {:?}", symbol, qty, side);
 
-                        let broker_tx = broker_tx.clone();
-                        tokio::spawn(async move {
-                            let _ = broker_tx
-                                .send(BrokerCommand::Flatten { qty, limit_px })
-                                .await;
-                            });
+                        outbox.push(BrokerCommand::Flatten { symbol, qty, limit_px });
                     }
                 }
 
-                OmsEvent::RiskKill { reason, qty, limit_px, } => {
-                    if oms.get_trading_state() != TradingState::Running {
+                OmsEvent::RiskKill { reason, legs } => {
+                    // a paused or reduce-only book can still be killed
+                    if !set_trading_state(
+                        &mut journal,
+                        &mut outcomes,
+                        &mut oms,
+                        TradingState::Flattening,
+                        &reason,
+                        false,
+                    ) {
                         continue;
                     }
 
                     warn!("[OMS][RISK] KILL SWITCH TRIGGERED: {}", reason);
 
-                    oms.set_trading_state(TradingState::Flattening);
-
                     // 1. cancel all live orders
-                    for order_id in oms.open_order_ids() {
-                        oms.request_cancel(order_id);
-
-                        let broker_tx = broker_tx.clone();
-                        tokio::spawn(async move {
-                            let _ = broker_tx
-                                .send(BrokerCommand::Cancel { order_id })
-                                .await;
-                            });
+                    for (order_id, _) in oms.open_orders(None) {
+                        cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
                     }
-                    if qty != dec!(0) {
-                        let _ = broker_tx
-                            .send(BrokerCommand::Flatten { qty, limit_px })
-                            .await;
+                    for FlattenLeg { symbol, qty, limit_px } in legs {
+                        if qty != dec!(0) {
+                            outbox.push(BrokerCommand::Flatten { symbol, qty, limit_px });
+                        }
                     }
 
-                    oms.set_trading_state(TradingState::Halted);
+                    set_trading_state(
+                        &mut journal,
+                        &mut outcomes,
+                        &mut oms,
+                        TradingState::Halted,
+                        &reason,
+                        false,
+                    );
 
                     warn!("[OMS][RISK] TRADING HALTED");
                 }
 
+                OmsEvent::PauseTrading { reason } => {
+                    if set_trading_state(&mut journal, &mut outcomes, &mut oms, TradingState::Paused, &reason, false) {
+                        for (order_id, _) in oms.open_orders(None) {
+                            cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
+                        }
+                    }
+                }
+
+                OmsEvent::ResumeTrading { reason } => {
+                    if oms.get_trading_state() == TradingState::Halted {
+                        warn!("[OMS][STATE] resume refused while halted, re-arm first: {}", reason);
+                        continue;
+                    }
+                    set_trading_state(&mut journal, &mut outcomes, &mut oms, TradingState::Running, &reason, false);
+                }
+
+                OmsEvent::ReduceOnly { reason } => {
+                    set_trading_state(&mut journal, &mut outcomes, &mut oms, TradingState::ReduceOnly, &reason, false);
+                }
+
+                OmsEvent::Rearm { operator, reason } => {
+                    let reason = format!("re-armed by {}: {}", operator, reason);
+                    set_trading_state(&mut journal, &mut outcomes, &mut oms, TradingState::Running, &reason, true);
+                }
+
                 OmsEvent::Tick => {
-                    info!("[OMS] tick → delta={}", oms.delta());
+                    let now = Instant::now();
+                    let unacked = oms.unacked_orders();
+
+                    for (order_id, symbol) in &unacked {
+                        let due = ack_clock
+                            .get(order_id)
+                            .is_none_or(|t| now.duration_since(*t) >= config.ack_timeout);
+                        if !due {
+                            continue;
+                        }
+
+                        if oms.order(*order_id).is_some_and(|o| o.state == OrderState::PendingAck) {
+                            warn!("[OMS] order {:?} not acked in {:?}", order_id, config.ack_timeout);
+                            let _ = commit(
+                                &mut journal,
+                                &mut outcomes,
+                                &mut oms,
+                                JournalEntry::StatusUnknown { order_id: *order_id },
+                            );
+                        }
+
+                        ack_clock.insert(*order_id, now);
+                        outbox.push(BrokerCommand::QueryStatus {
+                            order_id: *order_id,
+                            symbol: symbol.clone(),
+                        });
+                    }
+
+                    ack_clock.retain(|id, _| unacked.iter().any(|(u, _)| u == id));
+                    status_misses.retain(|id, _| unacked.iter().any(|(u, _)| u == id));
+
+                    cancel_retry.retain(|id, _| oms.order(*id).is_some_and(|o| !o.is_terminal()));
+                    let resend: Vec<OrderId> = cancel_retry
+                        .iter()
+                        .filter(|(id, (at, _))| {
+                            now.duration_since(*at) >= config.ack_timeout
+                                && oms.order(**id).is_some_and(|o| {
+                                    matches!(o.state, OrderState::Open { .. } | OrderState::PartiallyFilled { .. })
+                                })
+                        })
+                        .map(|(id, _)| *id)
+                        .collect();
+                    for order_id in resend {
+                        warn!("[OMS] sending refused cancel {:?} again", order_id);
+                        cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
+                    }
+
+                    exec_due.extend(executor.symbols());
+                    algo_due = true;
+
+                    // only a loop that is still turning refreshes the switch
+                    if let Some(timeout) = config.dead_man_timeout {
+                        outbox.push(BrokerCommand::Heartbeat { timeout });
+                    }
+                }
+            }
+
+            /* ---------- Algos ---------- */
+
+            // cancels go out even when halted; new children do not
+            if algo_due && oms.get_trading_state() == TradingState::Running {
+                algo_actions.extend(algos.plan(&oms, Instant::now()));
+            }
+            algo_due = false;
+
+            for action in algo_actions.drain(..) {
+                match action {
+                    AlgoAction::Place { order } => {
+                        let _ = place_order(&mut journal, &mut outcomes, &mut oms, &mut ack_clock, &mut outbox, *order);
+                    }
+                    AlgoAction::Cancel { order_id } => {
+                        cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
+                    }
+                }
+            }
+
+            /* ---------- Executor ---------- */
+
+            if oms.get_trading_state() != TradingState::Running {
+                exec_due.clear();
+            }
+
+            for symbol in exec_due.drain(..) {
+                for action in executor.plan(&oms, &symbol, Instant::now()) {
+                    match action {
+                        ExecAction::Place { order_id, symbol, side, qty, price, tif } => {
+                            let order = Order::new(&symbol, side, qty, price).with_id(order_id).with_tif(tif);
+                            let _ = place_order(&mut journal, &mut outcomes, &mut oms, &mut ack_clock, &mut outbox, order);
+                        }
+                        ExecAction::Amend { order_id, price, qty } => {
+                            amend_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id, price, qty);
+                        }
+                        ExecAction::Cancel { order_id } => {
+                            cancel_order(&mut journal, &mut outcomes, &mut oms, &mut outbox, order_id);
+                        }
+                    }
                 }
             }
         }
 
+        end_turn(&mut journal, &mut outcomes, &mut oms, &mut outbox);
         info!("[OMS] channel closed, exiting");
     });
 
 
-    OmsRuntime { sender: tx }
+    OmsRuntime { sender: tx, anomaly_tx, update_tx, instruments, broker }
 }
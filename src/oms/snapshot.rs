@@ -1,20 +1,63 @@
 use rust_decimal::Decimal;
-use crate::oms::order::{OrderId, Side, OrderState};
+use crate::oms::order::{OrderId, Side, OrderState, TimeInForce, OrderFlags, RejectReason};
 
 #[derive(Debug, Clone)]
 pub struct OrderView {
     pub id: OrderId,
+    pub symbol: String,
     pub side: Side,
     pub limit_price: Decimal,
+    pub tif: TimeInForce,
+    pub flags: OrderFlags,
+    pub tag: Option<String>,
     pub original_qty: Decimal,
     pub remaining_qty: Decimal,
     pub state: OrderState,
+    pub reject_reason: Option<RejectReason>,
 }
 
+#[derive(Debug, Clone)]
+pub struct PositionView {
+    pub symbol: String,
+    pub net_qty: Decimal,
+    pub avg_price: Decimal,
+    /// Gross, before fees
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub net_pnl: Decimal,
+    /// Notional traded as maker / taker
+    pub maker_volume: Decimal,
+    pub taker_volume: Decimal,
+    /// None until the market feed has priced the symbol
+    pub mark: Option<Decimal>,
+    pub unrealized_pnl: Decimal,
+    /// Net realized plus unrealized
+    pub total_pnl: Decimal,
+    /// Signed exposure at the mark
+    pub notional: Decimal,
+    pub delta: Decimal,
+}
+
+/// What one tag's orders have done on one symbol
+#[derive(Debug, Clone)]
+pub struct TagPositionView {
+    pub tag: String,
+    pub symbol: String,
+    /// Set when the tag is also a strategy with its own target
+    pub target: Option<Decimal>,
+    pub net_qty: Decimal,
+    pub avg_price: Decimal,
+    /// Gross, before fees
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub net_pnl: Decimal,
+    /// Notional traded, maker plus taker
+    pub volume: Decimal,
+}
 
 #[derive(Clone, Debug)]
 pub struct OmsSnapshot {
     pub orders: Vec<OrderView>,
-    pub net_position: Decimal,
-    pub avg_price: Decimal,
+    pub positions: Vec<PositionView>,
+    pub tags: Vec<TagPositionView>,
 }
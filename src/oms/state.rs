@@ -1,6 +1,56 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TradingState {
     Running,
+
+    /// Operator pause: no new orders or amends, live ones were cancelled
+    Paused,
+
+    /// Only orders that shrink |net_qty| are accepted
+    ReduceOnly,
+
     Flattening,
+
+    /// Kill switch or drift stop; only an explicit re-arm leaves it
     Halted,
 }
+
+impl TradingState {
+    /// Whether moving to `to` is allowed. Leaving `Halted` needs `rearm`,
+    /// an explicit operator action; nothing but `Halted` follows `Flattening`.
+    pub fn allows(self, to: TradingState, rearm: bool) -> bool {
+        use TradingState::*;
+
+        match (self, to) {
+            (from, to) if from == to => false,
+            (Halted, Running) => rearm,
+            (Halted, _) => false,
+            (Flattening, Halted) => true,
+            (Flattening, _) => false,
+            (Running | Paused | ReduceOnly, _) => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halted_needs_rearm_and_flattening_only_halts() {
+        use TradingState::*;
+
+        assert!(Running.allows(ReduceOnly, false));
+        assert!(ReduceOnly.allows(Running, false));
+        assert!(Paused.allows(Halted, false));
+        assert!(!Running.allows(Running, false));
+
+        assert!(!Halted.allows(Running, false));
+        assert!(Halted.allows(Running, true));
+        assert!(!Halted.allows(Paused, true));
+
+        assert!(Flattening.allows(Halted, false));
+        assert!(!Flattening.allows(Running, true));
+    }
+}
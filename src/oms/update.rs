@@ -0,0 +1,122 @@
+use super::account::AccountSnapshot;
+use super::engine::OmsEngine;
+use super::fill::Fill;
+use super::journal::JournalEntry;
+use super::ledger::LotRecord;
+use super::order::OrderId;
+use super::snapshot::{OrderView, PositionView};
+use super::state::TradingState;
+
+/// Something that changed inside the OMS, pushed to every subscriber.
+///
+/// Views are taken after the change, so a subscriber never has to ask the
+/// OMS what the order or position looks like now.
+#[derive(Debug, Clone)]
+pub enum OmsUpdate {
+    OrderCreated { order: OrderView },
+    OrderAccepted { order: OrderView },
+    OrderRejected { order: OrderView },
+    OrderFilled { fill: Fill, order: OrderView },
+
+    /// Cancel confirmed, or the venue expired the order
+    OrderCancelled { order: OrderView },
+
+    /// Any other order transition: sent, unacked, amend or cancel in flight
+    /// or refused, synced from the venue
+    OrderUpdated { order: OrderView },
+
+    PositionChanged { position: PositionView },
+
+    /// A fill closed (part of) an open lot; already in the ledger
+    LotClosed { record: LotRecord },
+
+    TradingStateChanged { state: TradingState, reason: String },
+    AccountUpdated { account: AccountSnapshot },
+}
+
+/// Updates caused by `entry`, once it has been applied to `engine`
+pub fn updates_for(entry: &JournalEntry, engine: &OmsEngine) -> Vec<OmsUpdate> {
+    let view = |id: &OrderId| engine.order(*id).map(|o| o.view());
+    let position = |symbol: &str| OmsUpdate::PositionChanged { position: engine.position_view(symbol) };
+
+    match entry {
+        JournalEntry::OrderCreated { order } => {
+            view(&order.id).map(|order| OmsUpdate::OrderCreated { order }).into_iter().collect()
+        }
+        JournalEntry::OrderAccepted { order_id } => {
+            view(order_id).map(|order| OmsUpdate::OrderAccepted { order }).into_iter().collect()
+        }
+        JournalEntry::OrderRejected { order_id, .. } => {
+            view(order_id).map(|order| OmsUpdate::OrderRejected { order }).into_iter().collect()
+        }
+        JournalEntry::Fill { fill } => match view(&fill.order_id) {
+            Some(order) => {
+                let symbol = order.symbol.clone();
+                vec![
+                    OmsUpdate::OrderFilled { fill: fill.clone(), order },
+                    position(&symbol),
+                ]
+            }
+            None => Vec::new(),
+        },
+        JournalEntry::CancelConfirmed { order_id } | JournalEntry::OrderExpired { order_id } => {
+            view(order_id).map(|order| OmsUpdate::OrderCancelled { order }).into_iter().collect()
+        }
+        JournalEntry::OrderSent { order_id }
+        | JournalEntry::StatusUnknown { order_id }
+        | JournalEntry::AmendRequested { order_id, .. }
+        | JournalEntry::AmendAccepted { order_id }
+        | JournalEntry::AmendRejected { order_id }
+        | JournalEntry::CancelRequested { order_id }
+        | JournalEntry::CancelRejected { order_id, .. } => {
+            view(order_id).map(|order| OmsUpdate::OrderUpdated { order }).into_iter().collect()
+        }
+        JournalEntry::OrderSynced { order } => {
+            view(&order.id).map(|order| OmsUpdate::OrderUpdated { order }).into_iter().collect()
+        }
+        JournalEntry::PositionSynced { symbol, .. } | JournalEntry::AdjustmentFill { symbol, .. } => {
+            vec![position(symbol)]
+        }
+        JournalEntry::SetTarget { symbol, .. } | JournalEntry::InternalCross { symbol, .. } => {
+            // delta moves with the target; per-strategy books with a cross
+            vec![position(symbol)]
+        }
+        JournalEntry::TradingState { state, reason } => {
+            vec![OmsUpdate::TradingStateChanged { state: *state, reason: reason.clone() }]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oms::order::{Order, Side};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn fill_reports_order_and_position() {
+        let mut engine = OmsEngine::new();
+        let order = Order::new("TST", Side::Buy, dec!(2), dec!(100));
+        let oid = order.id;
+
+        for entry in [
+            JournalEntry::OrderCreated { order },
+            JournalEntry::OrderAccepted { order_id: oid },
+            JournalEntry::Fill { fill: Fill::new(oid, dec!(1), dec!(100)) },
+        ] {
+            engine.apply(&entry).unwrap();
+        }
+
+        let fill = JournalEntry::Fill { fill: Fill::new(oid, dec!(1), dec!(101)) };
+        engine.apply(&fill).unwrap();
+
+        match updates_for(&fill, &engine).as_slice() {
+            [OmsUpdate::OrderFilled { order, .. }, OmsUpdate::PositionChanged { position }] => {
+                assert_eq!(order.remaining_qty, dec!(0));
+                assert_eq!(position.net_qty, dec!(2));
+                assert_eq!(position.avg_price, dec!(100.5));
+            }
+            other => panic!("unexpected updates {:?}", other),
+        }
+    }
+}
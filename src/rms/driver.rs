@@ -1,31 +1,40 @@
+use std::collections::HashMap;
 use std::time::Duration;
 use rust_decimal_macros::dec;
-use tokio::sync::{mpsc, oneshot, broadcast};
-use tokio::time::interval;
+use tokio::sync::broadcast;
+use tokio::time::sleep;
 use tracing::{info, warn};
 
 use crate::market::types::MarketEvent;
 use crate::market::types::MarketSnapshot;
-use crate::oms::event::OmsEvent;
+use crate::oms::error::{OmsAnomaly, OmsError};
+use crate::oms::handle::OmsHandle;
+use crate::oms::update::OmsUpdate;
 use crate::rms::engine::RiskEngine;
 use crate::rms::types::RiskConfig;
 
-/// Periodically polls account state and feeds RMS.
+/// Feeds RMS every account update the OMS pushes.
 /// This task NEVER places orders directly.
 pub async fn start_rms_driver(
     mut market_rx: broadcast::Receiver<MarketEvent>,
-    oms_tx: mpsc::Sender<OmsEvent>,
+    mut anomaly_rx: broadcast::Receiver<OmsAnomaly>,
+    oms: OmsHandle,
     poll_interval: Duration,
 ) -> anyhow::Result<()> {
 
-    // --- wait for first market snapshot ---
-    let mut last_snapshot: Option<MarketSnapshot> = None;
+    // --- latest market snapshot per symbol ---
+    let mut last_snapshots: HashMap<String, MarketSnapshot> = HashMap::new();
 
     // --- initial account snapshot (baseline equity) ---
-    let start_account = {
-        let (tx, rx) = oneshot::channel();
-        let _ = oms_tx.send(OmsEvent::GetAccountSnapshot { reply: tx }).await;
-        rx.await?
+    let start_account = loop {
+        match oms.account().await {
+            Ok(acct) => break acct,
+            Err(e @ (OmsError::NotReady(_) | OmsError::Timeout(_))) => {
+                info!("[RMS] waiting for account: {}", e);
+                sleep(poll_interval).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
     };
 
     info!("[RMS] starting with equity={}", start_account.equity);
@@ -34,38 +43,43 @@ pub async fn start_rms_driver(
         start_account.equity,
         RiskConfig {
             max_drawdown_pct: dec!(0.10), // 10%
+            max_order_anomalies: 20,
         },
-        oms_tx.clone(),
+        oms.clone(),
     );
 
-    let mut ticker = interval(poll_interval);
+    let mut updates = oms.subscribe();
 
     loop {
         tokio::select! {
             Ok(event) = market_rx.recv() => {
                 if let MarketEvent::Snapshot(s) = event {
-                    last_snapshot = Some(s);
+                    last_snapshots.insert(s.symbol.clone(), s);
                 }
             }
 
-            _ = ticker.tick() => {
-                let snapshot = match &last_snapshot {
-                    Some(s) => s,
-                    None => continue,
-                };
+            Ok(anomaly) = anomaly_rx.recv() => {
+                let acct = oms.account().await.ok();
 
-                let (tx, rx) = oneshot::channel();
-                let _ = oms_tx.send(OmsEvent::GetAccountSnapshot { reply: tx }).await;
+                rms.on_anomaly(&anomaly, acct.as_ref(), &last_snapshots).await;
+            }
 
-                match rx.await {
-                    Ok(acct) => {
-                        rms.on_snapshot(&acct, snapshot).await;
-                    }
-                    Err(e) => {
-                        warn!("[RMS] failed to fetch account snapshot: {:?}", e);
+            update = updates.recv() => match update {
+                Ok(OmsUpdate::AccountUpdated { account }) => {
+                    if last_snapshots.is_empty() {
+                        continue;
                     }
+                    rms.on_snapshot(&account, &last_snapshots).await;
                 }
-            }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("[RMS] missed {} OMS updates", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    warn!("[RMS] OMS update stream closed");
+                    return Ok(());
+                }
+            },
         }
     }
 
@@ -1,39 +1,43 @@
+use std::collections::HashMap;
+
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use tokio::sync::mpsc;
 use tracing::{info, warn};
 
-use crate::oms::event::OmsEvent;
+use crate::oms::event::FlattenLeg;
+use crate::oms::handle::OmsHandle;
 use crate::oms::account::AccountSnapshot;
+use crate::oms::error::OmsAnomaly;
 use crate::market::types::MarketSnapshot;
 use crate::rms::types::{RiskConfig, RiskState};
 
 pub struct RiskEngine {
     cfg: RiskConfig,
     state: RiskState,
-    oms_tx: mpsc::Sender<OmsEvent>,
+    oms: OmsHandle,
 }
 
 impl RiskEngine {
     pub fn new(
         start_equity: Decimal,
         cfg: RiskConfig,
-        oms_tx: mpsc::Sender<OmsEvent>,
+        oms: OmsHandle,
     ) -> Self {
         Self {
             cfg,
             state: RiskState {
                 start_equity,
                 killed: false,
+                anomalies: 0,
             },
-            oms_tx,
+            oms,
         }
     }
 
     pub async fn on_snapshot(
         &mut self,
         acct: &AccountSnapshot,
-        market: &MarketSnapshot,
+        markets: &HashMap<String, MarketSnapshot>,
     ) {
         if self.state.killed {
             return;
@@ -58,6 +62,72 @@ impl RiskEngine {
                 self.state.start_equity, equity, dd
             );
 
+            let legs = Self::flatten_legs(acct, markets);
+            let reason = format!("drawdown {} >= {}", dd, self.cfg.max_drawdown_pct);
+            if let Err(e) = self.oms.risk_kill(reason, legs).await {
+                warn!("[RMS] could not deliver kill: {}", e);
+            }
+        }
+    }
+
+    /// An event the OMS refused or booked with an anomaly. A few are expected
+    /// (late cancels, duplicate fills); a steady stream means our view of the
+    /// venue is wrong.
+    pub async fn on_anomaly(
+        &mut self,
+        anomaly: &OmsAnomaly,
+        acct: Option<&AccountSnapshot>,
+        markets: &HashMap<String, MarketSnapshot>,
+    ) {
+        if self.state.killed {
+            return;
+        }
+
+        self.state.anomalies += 1;
+
+        warn!(
+            "[RMS] OMS anomaly #{}: {} ({})",
+            self.state.anomalies, anomaly.error, anomaly.event
+        );
+
+        if self.state.anomalies > self.cfg.max_order_anomalies {
+            self.state.killed = true;
+
+            let legs = match acct {
+                Some(acct) => Self::flatten_legs(acct, markets),
+                None => Vec::new(),
+            };
+
+            let reason = format!(
+                "order anomalies {} > {}",
+                self.state.anomalies, self.cfg.max_order_anomalies
+            );
+            if let Err(e) = self.oms.risk_kill(reason, legs).await {
+                warn!("[RMS] could not deliver kill: {}", e);
+            }
+        }
+    }
+
+    fn flatten_legs(
+        acct: &AccountSnapshot,
+        markets: &HashMap<String, MarketSnapshot>,
+    ) -> Vec<FlattenLeg> {
+        let mut legs = Vec::new();
+
+        for (symbol, net_position) in &acct.positions {
+            let net_position = *net_position;
+            if net_position == dec!(0) {
+                continue;
+            }
+
+            let market = match markets.get(symbol) {
+                Some(m) => m,
+                None => {
+                    warn!("[RMS] no market data for {}, cannot price flatten", symbol);
+                    continue;
+                }
+            };
+
             let best_bid = match market.book.bids.first() {
                 Some(l) => l.price,
                 None => dec!(0),
@@ -68,7 +138,6 @@ impl RiskEngine {
                 None => dec!(0),
             };
 
-            let net_position = acct.net_position;
             let is_buy = net_position < dec!(0); // short → buy to flatten
 
             let extreme_price = if is_buy {
@@ -77,15 +146,14 @@ impl RiskEngine {
                 best_bid * dec!(0.95)   // cross the book downward
             };
 
-            let _ = self.oms_tx.send(OmsEvent::RiskKill {
-                reason: format!(
-                    "drawdown {} >= {}",
-                    dd, self.cfg.max_drawdown_pct
-                ),
+            legs.push(FlattenLeg {
+                symbol: symbol.clone(),
                 qty: net_position,
                 limit_px: extreme_price, // market flatten
-            }).await;
+            });
         }
+
+        legs
     }
 
 }
@@ -3,10 +3,12 @@ use rust_decimal::Decimal;
 #[derive(Debug, Clone)]
 pub struct RiskConfig {
     pub max_drawdown_pct: Decimal, // e.g. 0.10
+    pub max_order_anomalies: usize, // refused OMS events before kill
 }
 
 #[derive(Debug, Clone)]
 pub struct RiskState {
     pub start_equity: Decimal,
     pub killed: bool,
+    pub anomalies: usize,
 }
@@ -4,16 +4,18 @@ use std::time::{Duration, Instant};
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use tokio::sync::{broadcast, mpsc, oneshot};
-use tracing::info;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
 
+use crate::market::instrument::Instrument;
 use crate::market::types::{MarketEvent, Trade, AggressorSide};
-use crate::oms::event::OmsEvent;
-use crate::oms::order::Side;
+use crate::oms::handle::{OmsHandle, OrderRequest};
+use crate::oms::order::{OrderState, RejectReason, Side, TimeInForce};
+use crate::oms::snapshot::OrderView;
+use crate::oms::update::OmsUpdate;
 
 /* ===================== CONSTANTS ===================== */
 
-const MIN_NOTIONAL: Decimal = dec!(10);
 const SAFETY_MARGIN: Decimal = dec!(0.85);
 const MAX_ABS_QTY: Decimal = dec!(1160);
 
@@ -24,6 +26,12 @@ const TOXIC_COOLDOWN: Duration = Duration::from_secs(6);
 const MIN_PCT_MOVE: Decimal = dec!(0.0010);
 const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
 
+/// Tag on every quote, so refreshes only cancel our own orders
+const TAG: &str = "mm";
+
+/// Most extra ticks a quote backs off after post-only rejects
+const MAX_CROSS_WIDEN: u32 = 5;
+
 /* ===================== FLOW ===================== */
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -171,14 +179,43 @@ impl TradeFlow {
 
 /* ===================== HELPERS ===================== */
 
-fn snap_to_tick(px: Decimal, tick: Decimal) -> Decimal {
-    (px / tick).floor() * tick
-}
-
 fn pct_change(a: Decimal, b: Decimal) -> Decimal {
     if b == dec!(0) { dec!(0) } else { (a - b) / b }
 }
 
+/// Extra ticks to quote away from the touch after `update`: one more per
+/// post-only reject of ours, one less per quote that rests
+fn cross_widen(update: &OmsUpdate, symbol: &str, widen: u32) -> u32 {
+    match update {
+        OmsUpdate::OrderRejected { order } if order.symbol == symbol && order.tag.as_deref() == Some(TAG) => {
+            match &order.reject_reason {
+                Some(RejectReason::PostOnlyWouldCross) => {
+                    info!("[MM] {:?} quote would cross, widening", order.side);
+                    (widen + 1).min(MAX_CROSS_WIDEN)
+                }
+                Some(reason) => {
+                    warn!("[MM] {:?} quote rejected: {}", order.side, reason);
+                    widen
+                }
+                None => widen,
+            }
+        }
+        OmsUpdate::OrderAccepted { order } if order.symbol == symbol && order.tag.as_deref() == Some(TAG) => {
+            widen.saturating_sub(1)
+        }
+        _ => widen,
+    }
+}
+
+/// Placed or amended and not yet answered: the quote may already be on the
+/// book, so its side is left alone until the venue says
+fn is_settling(state: &OrderState) -> bool {
+    matches!(
+        state,
+        OrderState::New | OrderState::PendingAck | OrderState::Unknown | OrderState::ReplacePending { .. }
+    )
+}
+
 fn inventory_ratios(delta: Decimal) -> (Decimal, Decimal) {
     let k = dec!(0.5);
     let bid = (dec!(1.0) + delta * k).clamp(dec!(0), dec!(2));
@@ -189,10 +226,11 @@ fn inventory_ratios(delta: Decimal) -> (Decimal, Decimal) {
 /* ===================== MM LOOP ===================== */
 
 pub async fn run_mm_strategy(
+    symbol: String,
     mut market_rx: broadcast::Receiver<MarketEvent>,
-    oms_tx: mpsc::Sender<OmsEvent>,
+    oms: OmsHandle,
+    instrument: Instrument,
 ) {
-    let tick = dec!(0.0000001);
 
     let mut last_bid: Option<Decimal> = None;
     let mut last_ask: Option<Decimal> = None;
@@ -200,6 +238,9 @@ pub async fn run_mm_strategy(
 
     let mut flow = TradeFlow::new();
 
+    let mut updates = oms.subscribe();
+    let mut widen: u32 = 0;
+
     loop {
         let event = match market_rx.recv().await {
             Ok(e) => e,
@@ -208,12 +249,21 @@ pub async fn run_mm_strategy(
 
         match event {
             MarketEvent::Trade(t) => {
+                if t.symbol != symbol {
+                    continue;
+                }
                 info!("[MM] ON Trade {:?}", t);
                 flow.on_trade(t);
                 continue;
             }
 
+            MarketEvent::Mark(_) => continue,
+
             MarketEvent::Snapshot(snapshot) => {
+                if snapshot.symbol != symbol {
+                    continue;
+                }
+
                 let best_bid = match snapshot.book.bids.first() {
                     Some(l) => l.price,
                     None => continue,
@@ -228,19 +278,35 @@ pub async fn run_mm_strategy(
 
                 /* -------- ACCOUNT -------- */
 
-                let (tx, rx) = oneshot::channel();
-                let _ = oms_tx.send(OmsEvent::GetAccountSnapshot { reply: tx }).await;
-                let acct = match rx.await { Ok(a) => a, Err(_) => continue };
+                let acct = match oms.account().await {
+                    Ok(a) => a,
+                    Err(e) => {
+                        info!("[MM] no account: {}", e);
+                        continue;
+                    }
+                };
 
                 let available_margin = acct.available_margin * SAFETY_MARGIN;
 
+                /* -------- REJECTS -------- */
+
+                loop {
+                    match updates.try_recv() {
+                        Ok(update) => widen = cross_widen(&update, &symbol, widen),
+                        Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                        Err(_) => break,
+                    }
+                }
+
                 /* -------- SPREAD -------- */
 
                 let half_spread = mid * dec!(0.0005) / dec!(2);
-                let bid = snap_to_tick(mid - half_spread, tick)
-                    .min(snap_to_tick(best_bid - tick, tick));
-                let ask = snap_to_tick(mid + half_spread, tick)
-                    .max(snap_to_tick(best_ask + tick, tick));
+                let tick = instrument.tick_at(mid);
+                let away = tick * Decimal::from(1 + widen);
+                let bid = instrument.round_price(mid - half_spread, Side::Buy)
+                    .min(instrument.round_price(best_bid - away, Side::Buy));
+                let ask = instrument.round_price(mid + half_spread, Side::Sell)
+                    .max(instrument.round_price(best_ask + away, Side::Sell));
 
                 if bid >= ask {
                     continue;
@@ -248,9 +314,7 @@ pub async fn run_mm_strategy(
 
                 /* -------- INVENTORY -------- */
 
-                let (tx, rx) = oneshot::channel();
-                let _ = oms_tx.send(OmsEvent::GetDelta { reply: tx }).await;
-                let delta = rx.await.unwrap_or(dec!(0));
+                let delta = oms.delta(&symbol).await.unwrap_or(dec!(0));
 
                 let (bid_ratio, ask_ratio) = inventory_ratios(delta);
                 info!("[MM] ratios are {} {}", bid_ratio, ask_ratio);
@@ -293,15 +357,15 @@ pub async fn run_mm_strategy(
                 let mut bid_qty = max_total_qty * bid_w;
                 let mut ask_qty = max_total_qty * ask_w;
 
-                bid_qty = bid_qty.min(available_margin / bid);
-                ask_qty = ask_qty.min(available_margin / ask);
+                bid_qty = instrument.round_qty(bid_qty.min(available_margin / bid));
+                ask_qty = instrument.round_qty(ask_qty.min(available_margin / ask));
 
                 info!("[MM] qts are {} {} {}", max_total_qty, bid_qty, ask_qty);
 
-                if bid_qty * bid < MIN_NOTIONAL {
+                if !instrument.meets_min_notional(bid_qty, bid) {
                     bid_qty = dec!(0);
                 }
-                if ask_qty * ask < MIN_NOTIONAL {
+                if !instrument.meets_min_notional(ask_qty, ask) {
                     ask_qty = dec!(0);
                 }
 
@@ -323,33 +387,92 @@ pub async fn run_mm_strategy(
                     continue;
                 }
 
+                /* -------- EXEC -------- */
+
+                let ours: Vec<OrderView> = match oms.snapshot().await {
+                    Ok(s) => s.orders,
+                    Err(_) => continue,
+                }
+                .into_iter()
+                .filter(|o| o.symbol == symbol && o.tag.as_deref() == Some(TAG))
+                .collect();
+
+                let settling = |side: Side| ours.iter().any(|o| o.side == side && is_settling(&o.state));
+                let (bid_settling, ask_settling) = (settling(Side::Buy), settling(Side::Sell));
+
+                let live = |side: Side| -> Vec<&OrderView> {
+                    ours.iter()
+                        .filter(|o| o.side == side)
+                        .filter(|o| matches!(
+                            o.state,
+                            OrderState::Open { .. } | OrderState::PartiallyFilled { .. }
+                        ))
+                        .collect()
+                };
+                let (live_bid, live_ask) = (live(Side::Buy), live(Side::Sell));
+
+                // amend in place when each side has at most one resting quote
+                // and we still want to quote that side — keeps queue priority
+                let can_amend = live_bid.len() <= 1
+                    && live_ask.len() <= 1
+                    && (live_bid.is_empty() || bid_qty > dec!(0))
+                    && (live_ask.is_empty() || ask_qty > dec!(0));
+
+                // a cancel-all would catch the unanswered quote too; wait
+                // for it instead, and retry on the next book
+                if !can_amend && (bid_settling || ask_settling) {
+                    info!("[MM] quotes in flight, requote deferred");
+                    continue;
+                }
+
                 last_bid = Some(bid);
                 last_ask = Some(ask);
                 last_refresh = Instant::now();
 
                 info!(
-                    "[MM][{:?}] bid={}({}) ask={}({}) delta={}",
-                    effective_flow, bid, bid_qty, ask, ask_qty, delta
+                    "[MM][{}][{:?}] bid={}({}) ask={}({}) delta={}",
+                    symbol, effective_flow, bid, bid_qty, ask, ask_qty, delta
                 );
 
-                /* -------- EXEC -------- */
-
-                let _ = oms_tx.send(OmsEvent::CancelAll).await;
+                if !can_amend {
+                    if let Err(e) = oms.cancel_all(Some(&symbol), Some(TAG)).await {
+                        warn!("[MM] cancel all failed: {}", e);
+                    }
+                }
 
-                if bid_qty > dec!(0) {
-                    let _ = oms_tx.send(OmsEvent::CreateOrder {
-                        side: Side::Buy,
-                        qty: bid_qty,
-                        price: bid,
-                    }).await;
+                let mut quotes = Vec::new();
+                for (side, qty, price, resting, in_flight) in [
+                    (Side::Buy, bid_qty, bid, live_bid.first(), bid_settling),
+                    (Side::Sell, ask_qty, ask, live_ask.first(), ask_settling),
+                ] {
+                    if in_flight {
+                        info!("[MM] {:?} quote in flight, side skipped", side);
+                        continue;
+                    }
+                    match resting {
+                        Some(o) if can_amend => {
+                            if let Err(e) = oms.amend(o.id, price, qty).await {
+                                warn!("[MM] amend {} failed: {}", o.id, e);
+                            }
+                        }
+                        _ if qty > dec!(0) => {
+                            quotes.push(
+                                OrderRequest::limit(&symbol, side, qty, price)
+                                    // post-only: a quote must never cross
+                                    .with_tif(TimeInForce::Alo)
+                                    .with_tag(TAG),
+                            );
+                        }
+                        _ => {}
+                    }
                 }
 
-                if ask_qty > dec!(0) {
-                    let _ = oms_tx.send(OmsEvent::CreateOrder {
-                        side: Side::Sell,
-                        qty: ask_qty,
-                        price: ask,
-                    }).await;
+                // both sides in one go, so they reach the venue together
+                let sides: Vec<Side> = quotes.iter().map(|q| q.side).collect();
+                for (side, res) in sides.into_iter().zip(oms.place_all(quotes).await) {
+                    if let Err(e) = res {
+                        warn!("[MM] {:?} quote not placed: {}", side, e);
+                    }
                 }
             }
         }
@@ -6,6 +6,7 @@ pub struct TuiApp {
     pub snapshot: Option<OmsSnapshot>,
 
     // strategy diagnostics (fed from market/strategy channel later)
+    pub symbol: String,
     pub mid: Decimal,
     pub spread: Decimal,
     pub skew: Decimal,
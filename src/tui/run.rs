@@ -1,25 +1,26 @@
-use tokio::sync::{mpsc, oneshot, broadcast};
-
-use rust_decimal::Decimal;
+use tokio::sync::broadcast::{self, error::TryRecvError};
 
 use rust_decimal_macros::dec;
+use crate::market::instrument::InstrumentRegistry;
 use crate::market::types::MarketEvent;
+use crate::oms::order::Side;
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::io::stdout;
 use ratatui::crossterm::{terminal, execute};
 use ratatui::crossterm::event::{self, Event, KeyCode};
 
-use crate::oms::event::OmsEvent;
+use crate::oms::handle::OmsHandle;
 use crate::tui::{app::TuiApp, ui::draw};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-fn snap_to_tick(price: Decimal, tick: Decimal) -> Decimal {
-    (price / tick).floor() * tick
-}
+/// How stale marked PnL may get when nothing else changes
+const MARK_REFRESH: Duration = Duration::from_secs(1);
 
 pub async fn run_tui(
-    oms_tx: mpsc::Sender<OmsEvent>,
+    oms: OmsHandle,
     mut market_rx: broadcast::Receiver<MarketEvent>,
+    instruments: Arc<InstrumentRegistry>,
 ) -> anyhow::Result<()> {
     terminal::enable_raw_mode()?;
         execute!(stdout(), terminal::EnterAlternateScreen)?;
@@ -28,6 +29,8 @@ pub async fn run_tui(
         let mut terminal = Terminal::new(backend)?;
 
         let mut app = TuiApp::default();
+        let mut updates = oms.subscribe();
+        let mut refreshed_at: Option<Instant> = None;
 
         let res = loop {
             if event::poll(Duration::from_millis(50))? {
@@ -45,11 +48,20 @@ pub async fn run_tui(
                 }
             }
 
-            // poll OMS snapshot
-            let (tx, rx) = tokio::sync::oneshot::channel();
-            let _ = oms_tx.send(OmsEvent::GetSnapshot { reply: tx }).await;
-            if let Ok(snapshot) = rx.await {
-                app.snapshot = Some(snapshot);
+            // refresh the OMS view when it pushed a change; marks move
+            // without one, so PnL is also refreshed once a second
+            let mut changed = refreshed_at.is_none_or(|t| t.elapsed() >= MARK_REFRESH);
+            while let Ok(_) | Err(TryRecvError::Lagged(_)) = updates.try_recv() {
+                changed = true;
+            }
+            if changed {
+                if let Ok(snapshot) = oms.snapshot().await {
+                    app.snapshot = Some(snapshot);
+                    refreshed_at = Some(Instant::now());
+                }
+            }
+
+            {
                 let snapshot = match market_rx.try_recv() {
                     Ok(MarketEvent::Snapshot(s)) => s,
                     _ => continue,
@@ -70,20 +82,18 @@ pub async fn run_tui(
                 let spread = dec!(20)*(best_ask - best_bid);
 
                 // query inventory delta
-                let (tx, rx) = oneshot::channel();
-                oms_tx
-                    .send(OmsEvent::GetDelta { reply: tx })
-                    .await
-                    .unwrap();
-                let delta = rx.await.unwrap();
+                let delta = oms.delta(&snapshot.symbol).await.unwrap_or(dec!(0));
                 let skew = delta * dec!(0.05);
 
                 let raw_bid = mid - spread / dec!(2) + skew;
                 let raw_ask = mid + spread / dec!(2) + skew;
 
-                let bid = snap_to_tick(raw_bid, dec!(1));
-                let ask = snap_to_tick(raw_ask, dec!(1));
+                let (bid, ask) = match instruments.get(&snapshot.symbol) {
+                    Some(i) => (i.round_price(raw_bid, Side::Buy), i.round_price(raw_ask, Side::Sell)),
+                    None => (raw_bid, raw_ask),
+                };
 
+                app.symbol = snapshot.symbol.clone();
                 app.mid = mid;
                 app.spread = spread;
                 app.skew = skew;
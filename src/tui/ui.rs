@@ -18,8 +18,8 @@ pub fn draw(f: &mut Frame, app: &TuiApp) {
 
     // --- STRATEGY ---
     let strategy = Paragraph::new(format!(
-            "Mid: {}\nSpread: {}\nSkew: {}\nBid: {}\nAsk: {}",
-            app.mid, app.spread, app.skew, app.bid, app.ask
+            "Symbol: {}\nMid: {}\nSpread: {}\nSkew: {}\nBid: {}\nAsk: {}",
+            app.symbol, app.mid, app.spread, app.skew, app.bid, app.ask
     ))
         .block(Block::default().title("Strategy").borders(Borders::ALL));
     f.render_widget(strategy, chunks[0]);
@@ -28,6 +28,8 @@ pub fn draw(f: &mut Frame, app: &TuiApp) {
     let rows = app.snapshot.iter().flat_map(|s| &s.orders).map(|o| {
         Row::new(vec![
             o.id.to_string(),
+            o.symbol.clone(),
+            o.tag.clone().unwrap_or_default(),
             format!("{:?}", o.side),
             o.limit_price.to_string(),
             o.original_qty.to_string(),
@@ -39,6 +41,8 @@ pub fn draw(f: &mut Frame, app: &TuiApp) {
         rows,
         [
         Constraint::Length(36), // OrderId
+        Constraint::Length(8),  // Symbol
+        Constraint::Length(8),  // Tag
         Constraint::Length(4),  // Side
         Constraint::Length(10), // Price
         Constraint::Length(10), // Orig qty
@@ -62,10 +66,14 @@ pub fn draw(f: &mut Frame, app: &TuiApp) {
 
     // --- POSITION ---
     if let Some(s) = &app.snapshot {
-        let pos = Paragraph::new(format!(
-                "Net: {}\nAvg Px: {}",
-                s.net_position, s.avg_price
-        ))
+        let lines: Vec<String> = s.positions.iter().map(|p| {
+            format!(
+                "{}  Net: {}  Avg Px: {}  PnL: {}  Fees: {}  uPnL: {}  Total: {}  Ntl: {}  Delta: {}",
+                p.symbol, p.net_qty, p.avg_price, p.realized_pnl, p.fees, p.unrealized_pnl,
+                p.total_pnl, p.notional, p.delta
+            )
+        }).collect();
+        let pos = Paragraph::new(lines.join("\n"))
             .block(Block::default().title("Position").borders(Borders::ALL));
         f.render_widget(pos, chunks[2]);
     }